@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use bevy::prelude::*;
 use bevy::ecs::event::Events;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 
 #[derive(Clone, Copy)]
 pub struct SoundRange {
@@ -24,14 +25,13 @@ impl SoundRange {
         Self {
             range: true,
             min: min.min(max),
-            max: max
+            max
         }
     }
 
     /// Gets a random value within in the range
-    pub fn get_random_value(&self) -> f32 {
+    pub fn get_random_value(&self, rng: &mut impl Rng) -> f32 {
         if self.range {
-            let mut rng = rand::thread_rng();
             rng.gen_range(self.min..=self.max)
         }
         else {
@@ -61,6 +61,7 @@ impl SoundSettings {
     };
 
     /// Non-randomized sound that loops
+    #[allow(dead_code)]
     pub const LOOP: SoundSettings = SoundSettings {
         repeat: true,
         volume_range: SoundRange::constant(1.0),
@@ -68,26 +69,27 @@ impl SoundSettings {
     };
 }
 
-#[derive(Hash, std::cmp::Eq, Clone, Copy)]
+#[derive(Hash, PartialEq, Eq, Clone, Copy)]
 pub enum Sound {
     GameBegin,
     PiecePlace,
+    HardDrop,
     LineClear,
     TetrisClear,
+    TSpin,
+    PerfectClear,
     GameOver
 }
-impl PartialEq for Sound {
-    fn eq(&self, other: &Self) -> bool {
-        *self as i32 == *other as i32
-    }
-}
 impl Sound {
     fn get_filename(&self) -> &str {
         match self {
             Sound::GameBegin => "game_begin.ogg",
             Sound::PiecePlace => "piece_place.ogg",
+            Sound::HardDrop => "piece_place_2.ogg",
             Sound::LineClear => "line_clear_2.ogg",
             Sound::TetrisClear => "tetris_clear_2.ogg",
+            Sound::TSpin => "tetris_clear.ogg",
+            Sound::PerfectClear => "line_clear.ogg",
             Sound::GameOver => "game_over_3.ogg"
         }
     }
@@ -99,6 +101,11 @@ impl Sound {
                 volume_range: SoundRange::constant(1.0),
                 speed_range: SoundRange::from_limits(0.8, 1.2)
             },
+            Sound::HardDrop => SoundSettings {
+                repeat: false,
+                volume_range: SoundRange::constant(1.0),
+                speed_range: SoundRange::from_limits(0.9, 1.1)
+            },
             Sound::GameOver => SoundSettings {
                 volume_range: SoundRange::constant(0.6),
                 ..Default::default()
@@ -115,6 +122,9 @@ impl Sound {
 // RESOURCES
 pub struct SoundAudios(HashMap<Sound, Handle<AudioSource>>);
 
+/// The randomness used for sounds, kept apart from the game's own so that it never changes the piece sequence
+pub struct AudioRng(StdRng);
+
 // EVENTS
 pub struct PlaySoundEvent {
     pub sound: Sound
@@ -123,17 +133,20 @@ pub struct PlaySoundEvent {
 fn load_sounds(asset_server: Res<AssetServer>, mut sound_audios: ResMut<SoundAudios>) {
     sound_audios.0.insert(Sound::GameBegin, asset_server.load(&*format!("sounds/{}", Sound::GameBegin.get_filename())));    
     sound_audios.0.insert(Sound::PiecePlace, asset_server.load(&*format!("sounds/{}", Sound::PiecePlace.get_filename())));
+    sound_audios.0.insert(Sound::HardDrop, asset_server.load(&*format!("sounds/{}", Sound::HardDrop.get_filename())));
     sound_audios.0.insert(Sound::LineClear, asset_server.load(&*format!("sounds/{}", Sound::LineClear.get_filename())));
     sound_audios.0.insert(Sound::TetrisClear, asset_server.load(&*format!("sounds/{}", Sound::TetrisClear.get_filename())));
+    sound_audios.0.insert(Sound::TSpin, asset_server.load(&*format!("sounds/{}", Sound::TSpin.get_filename())));
+    sound_audios.0.insert(Sound::PerfectClear, asset_server.load(&*format!("sounds/{}", Sound::PerfectClear.get_filename())));
     sound_audios.0.insert(Sound::GameOver, asset_server.load(&*format!("sounds/{}", Sound::GameOver.get_filename())));
 }
 
-fn play_sound(audio: Res<Audio>, mut play_sound_listener: EventReader<PlaySoundEvent>, sound_audios: Res<SoundAudios>) {
+fn play_sound(audio: Res<Audio>, mut play_sound_listener: EventReader<PlaySoundEvent>, sound_audios: Res<SoundAudios>, mut audio_rng: ResMut<AudioRng>) {
     for event in play_sound_listener.iter() {
         match sound_audios.0.get(&event.sound) {
             Some(sound) => { 
                 let sound_settings = event.sound.get_settings();
-                audio.play_with_settings(sound.clone(), PlaybackSettings { repeat: sound_settings.repeat, volume: sound_settings.volume_range.get_random_value(), speed: sound_settings.speed_range.get_random_value() }); 
+                audio.play_with_settings(sound.clone(), PlaybackSettings { repeat: sound_settings.repeat, volume: sound_settings.volume_range.get_random_value(&mut audio_rng.0), speed: sound_settings.speed_range.get_random_value(&mut audio_rng.0) }); 
             },
             None => { return; }
         }
@@ -146,6 +159,7 @@ impl Plugin for AudioPlugin {
         app
             .init_resource::<Events<PlaySoundEvent>>()
             .insert_resource(SoundAudios(HashMap::new()))
+            .insert_resource(AudioRng(StdRng::from_entropy()))
             .add_startup_system(load_sounds)
             .add_system(play_sound);
     }
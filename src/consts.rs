@@ -1,31 +1,32 @@
-/// Width of the game board (in segments)
+/// Default width of the game board (in segments)
 pub const BOARD_WIDTH: usize = 10;
 
-/// Height of the game board (in segments)
+/// Default height of the game board (in segments)
 pub const BOARD_HEIGHT: usize = 20;
 
-/// Size of a piece segment
-pub const SEGMENT_SIZE: f32 = 40.0;
+/// Default amount of hidden rows above the board that pieces spawn in
+pub const VANISH_ROWS: usize = 2;
 
 // MOVEMENT
-/// The delay between movements when holding a side movement key down
-pub const MOVE_DELAY: f32 = 0.125;
-/// The delay between movements when holding the down key
-pub const DROP_DELAY: f32 = 0.05;
+/// How long a side movement key has to be held before the piece starts moving by itself (delayed auto shift)
+pub const AUTO_SHIFT_DELAY: f32 = 0.125;
+/// The delay between movements once the piece has started moving by itself (auto repeat rate)
+pub const AUTO_REPEAT_RATE: f32 = 0.125;
+/// How many times faster than gravity a piece falls when holding the down key
+pub const SOFT_DROP_FACTOR: f32 = 20.0;
 
 // GAMEPLAY
-/// The delay between each time that pieces fall on level 0 (lower on further levels)
-pub const FALL_DELAY: f32 = 1.0;
-/// The piece spawn X coordinate, in board coordinates
-pub const PIECE_SPAWN_XP: i32 = BOARD_WIDTH as i32 / 2 - 1;
-/// The piece spawn Y coordinate, in board coordinates
-pub const PIECE_SPAWN_YP: i32 = BOARD_HEIGHT as i32 - 2; 
-/// The amount of score rewarded for moving a piece down yourself on level 0
-pub const PLACE_SCORE_BASE: usize = 1;
-/// The amount of score rewarded for a single line at level 0
-pub const LINE_SCORE_BASE: usize = 50;
-pub const TETRIS_MULTIPLIER: usize = 26;
+/// How long a piece can stay on the ground before it gets placed
+pub const LOCK_DELAY: f32 = 0.5;
+/// How long it takes for the next piece to appear after a piece has been placed (entry delay, or ARE)
+pub const ENTRY_DELAY: f32 = 0.1;
+/// How long cleared lines stay on the board before they disappear
+pub const LINE_CLEAR_DELAY: f32 = 0.3;
+/// How long (in seconds) before the next piece spawns keys pressed while there is no piece are remembered for it
+pub const INPUT_BUFFER_TIME: f32 = 0.15;
+/// How many times moving or rotating a piece on the ground can restart the lock delay, with move reset
+pub const LOCK_RESET_MOVES: usize = 15;
 /// The amount of lines that must be cleared in order to progress to the next level
 pub const LINES_PER_LEVEL: usize = 10;
-/// The percentage that speed increases by per level (might have to make this a flat value i dunno)
-pub const LEVEL_SPEED_INCREASE_PERCENTAGE: f32 = 1.26;
\ No newline at end of file
+/// The largest amount of upcoming pieces that can be shown
+pub const MAX_NEXT_QUEUE_LENGTH: usize = 6;
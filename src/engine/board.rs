@@ -0,0 +1,195 @@
+use crate::consts::*;
+use super::piece::Shape;
+
+/// The size of the board and where pieces spawn on it
+#[derive(Clone, Debug)]
+pub struct BoardConfig {
+    /// Width of the game board (in segments)
+    pub width: usize,
+    /// Height of the visible part of the game board (in segments)
+    pub height: usize,
+    /// How many hidden rows there are above the visible part of the board, where pieces spawn (at least 2, more if the pieces are taller when they spawn)
+    pub vanish_rows: usize,
+    /// The piece spawn X coordinate, in board coordinates
+    pub spawn_x: i32,
+    /// The row that the lowest segments of a spawned piece are on, in board coordinates
+    pub spawn_y: i32
+}
+impl Default for BoardConfig {
+    fn default() -> Self {
+        BoardConfig::new(BOARD_WIDTH, BOARD_HEIGHT)
+    }
+}
+
+impl BoardConfig {
+    /// Creates the config for a board of the given size, with pieces spawning in the vanish zone right above the center
+    pub fn new(width: usize, height: usize) -> Self {
+        BoardConfig {
+            width,
+            height,
+            vanish_rows: VANISH_ROWS,
+            spawn_x: width as i32 / 2 - 1,
+            spawn_y: height as i32
+        }
+    }
+}
+
+/// Stores the current game board state (full / empty squares)
+#[derive(Clone, Debug)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    vanish_rows: usize,
+    /// The shape that filled each cell, row by row starting from the bottom (including the vanish zone)
+    cells: Vec<Option<Shape>>
+}
+
+impl Board {
+    /// Creates an empty board with the given visible size and the given amount of hidden rows above it
+    pub fn new(width: usize, height: usize, vanish_rows: usize) -> Self {
+        Board { width, height, vanish_rows, cells: vec![None; width * (height + vanish_rows)] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the visible part of the board
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The height of the board including the vanish zone
+    pub fn total_height(&self) -> usize {
+        self.height + self.vanish_rows
+    }
+
+    /// Checks whether or not a certain position is within the board's boundaries (the vanish zone is part of the board)
+    pub fn is_on_board(&self, x: i32, y: i32, ignore_upper_boundary: bool) -> bool {
+        x >= 0 && x < self.width as i32 && y >= 0 && (ignore_upper_boundary || y < self.total_height() as i32)
+    }
+
+    /// Gets the shape that filled the cell at the given position, if there is one
+    pub fn get(&self, x: i32, y: i32) -> Option<Shape> {
+        if self.is_on_board(x, y, false) {
+            self.cells[y as usize * self.width + x as usize]
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether a segment can be at the given position (the space above the vanish zone is never free)
+    pub fn is_free(&self, x: i32, y: i32) -> bool {
+        self.is_on_board(x, y, false) && self.get(x, y).is_none()
+    }
+
+    /// Fills the cell at the given position, positions outside of the board are ignored
+    pub fn set(&mut self, x: i32, y: i32, shape: Option<Shape>) {
+        if self.is_on_board(x, y, false) {
+            self.cells[y as usize * self.width + x as usize] = shape;
+        }
+    }
+
+    /// Checks whether every cell in a row is filled
+    pub fn is_line_full(&self, y: usize) -> bool {
+        self.cells[y * self.width..(y + 1) * self.width].iter().all(|cell| cell.is_some())
+    }
+
+    /// Gets the indices of every full line, from the bottom
+    pub fn full_lines(&self) -> Vec<usize> {
+        (0..self.total_height()).filter(|&y| self.is_line_full(y)).collect()
+    }
+
+    /// Checks whether the board would be completely empty after removing every full line (a perfect clear)
+    pub fn is_empty_without_full_lines(&self) -> bool {
+        (0..self.total_height()).all(|y| self.is_line_full(y) || self.cells[y * self.width..(y + 1) * self.width].iter().all(|cell| cell.is_none()))
+    }
+
+    /// Removes every full line and makes the lines above them fall, returns the indices of the removed lines
+    pub fn clear_lines(&mut self) -> Vec<usize> {
+        let full_lines = self.full_lines();
+
+        // go through the rows from the bottom, moving every remaining row down by the amount of lines cleared below it
+        let mut target_row = 0;
+        for y in 0..self.total_height() {
+            if full_lines.contains(&y) {
+                continue;
+            }
+            if target_row != y {
+                self.cells.copy_within(y * self.width..(y + 1) * self.width, target_row * self.width);
+            }
+            target_row += 1;
+        }
+        // the rows at the top are now empty
+        for cell in self.cells[target_row * self.width..].iter_mut() {
+            *cell = None;
+        }
+
+        full_lines
+    }
+
+    /// Empties the whole board
+    pub fn clear(&mut self) {
+        self.cells.fill(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_lines_removes_full_lines_and_moves_the_rest_down() {
+        let mut board = Board::new(4, 4, 2);
+        for x in 0..4 {
+            board.set(x, 0, Some(Shape(0)));
+            board.set(x, 2, Some(Shape(0)));
+        }
+        board.set(1, 1, Some(Shape(1)));
+        board.set(3, 3, Some(Shape(2)));
+
+        assert_eq!(board.clear_lines(), vec![0, 2]);
+        assert_eq!(board.get(1, 0), Some(Shape(1)));
+        assert_eq!(board.get(3, 1), Some(Shape(2)));
+        assert!(board.full_lines().is_empty());
+        assert!((2..board.total_height() as i32).all(|y| (0..4).all(|x| board.get(x, y).is_none())));
+    }
+
+    #[test]
+    fn clear_lines_without_full_lines_changes_nothing() {
+        let mut board = Board::new(4, 4, 2);
+        board.set(0, 0, Some(Shape(0)));
+
+        assert!(board.clear_lines().is_empty());
+        assert_eq!(board.get(0, 0), Some(Shape(0)));
+    }
+
+    #[test]
+    fn is_free_only_inside_the_board() {
+        let mut board = Board::new(4, 4, 2);
+        board.set(1, 0, Some(Shape(0)));
+
+        assert!(board.is_free(0, 0));
+        assert!(!board.is_free(1, 0));
+        assert!(board.is_free(3, 5));
+        assert!(!board.is_free(3, 6));
+        assert!(!board.is_free(-1, 0));
+        assert!(!board.is_free(4, 0));
+        assert!(!board.is_free(0, -1));
+    }
+
+    #[test]
+    fn is_empty_without_full_lines_ignores_only_full_lines() {
+        let mut board = Board::new(4, 4, 2);
+        assert!(board.is_empty_without_full_lines());
+
+        for x in 0..4 {
+            board.set(x, 0, Some(Shape(0)));
+            board.set(x, 1, Some(Shape(0)));
+        }
+        assert!(board.is_empty_without_full_lines());
+
+        board.set(0, 2, Some(Shape(0)));
+        assert!(!board.is_empty_without_full_lines());
+    }
+}
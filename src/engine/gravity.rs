@@ -0,0 +1,82 @@
+//! Gravity curves decide how fast pieces fall on each level
+
+/// How many frames a second the original games ran at, which their gravity is measured in
+const FRAMES_PER_SECOND: f32 = 60.0;
+/// The NES ran slightly faster than 60 frames a second
+const NES_FRAMES_PER_SECOND: f32 = 60.0988;
+
+/// How many frames it takes for a piece to fall one row on the NES, from each level on
+const NES_FRAMES_PER_ROW: [(usize, f32); 15] = [
+    (0, 48.0), (1, 43.0), (2, 38.0), (3, 33.0), (4, 28.0), (5, 23.0), (6, 18.0), (7, 13.0), (8, 8.0), (9, 6.0),
+    (10, 5.0), (13, 4.0), (16, 3.0), (19, 2.0), (29, 1.0)
+];
+
+/// The internal gravity of TGM from each level on, in 1/256 rows per frame (256 is one row every frame, 5120 is 20G)
+const TGM_INTERNAL_GRAVITY: [(usize, f32); 30] = [
+    (0, 4.0), (30, 6.0), (35, 8.0), (40, 10.0), (50, 12.0), (60, 16.0), (70, 32.0), (80, 48.0), (90, 64.0), (100, 80.0),
+    (120, 96.0), (140, 112.0), (160, 128.0), (170, 144.0), (200, 4.0), (220, 32.0), (230, 64.0), (233, 96.0), (236, 128.0), (239, 160.0),
+    (243, 192.0), (247, 224.0), (251, 256.0), (300, 512.0), (330, 768.0), (360, 1024.0), (400, 1280.0), (420, 1024.0), (450, 768.0), (500, 5120.0)
+];
+
+/// The Guideline formula stops being usable not long after this level, so it doesn't get any faster
+const GUIDELINE_MAX_LEVEL: usize = 20;
+
+/// The named ways that the fall speed can depend on the level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GravityCurve {
+    /// The frames per row table of the NES version, one row every frame from level 29 on
+    Nes,
+    /// The modern formula, (0.8 - ((level - 1) * 0.007)) ^ (level - 1) seconds per row
+    Guideline,
+    /// The internal gravity table of TGM, which slows down again at level 200 and reaches 20G at level 500.
+    /// TGM levels go up to 999, so the fastest speeds need a high starting level
+    Tgm
+}
+impl GravityCurve {
+    /// How many seconds it takes for a piece to fall one row on the given level.
+    /// Below one frame, more than one row is fallen every frame (20G falls to the floor right away)
+    pub fn fall_delay(&self, level: usize) -> f32 {
+        match self {
+            GravityCurve::Nes => lookup(&NES_FRAMES_PER_ROW, level) / NES_FRAMES_PER_SECOND,
+            GravityCurve::Guideline => {
+                let level = level.clamp(1, GUIDELINE_MAX_LEVEL) as f32;
+                (0.8 - ((level - 1.0) * 0.007)).powf(level - 1.0)
+            },
+            GravityCurve::Tgm => 256.0 / (lookup(&TGM_INTERNAL_GRAVITY, level) * FRAMES_PER_SECOND)
+        }
+    }
+}
+
+/// Gets the value of the last entry in a table (sorted by level) that starts on or before the given level
+fn lookup(table: &[(usize, f32)], level: usize) -> f32 {
+    table.iter().rev().find(|(from_level, _)| level >= *from_level).map_or(table[0].1, |(_, value)| *value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nes_gravity_follows_the_frame_table() {
+        assert_eq!(GravityCurve::Nes.fall_delay(0), 48.0 / NES_FRAMES_PER_SECOND);
+        assert_eq!(GravityCurve::Nes.fall_delay(11), 5.0 / NES_FRAMES_PER_SECOND);
+        assert_eq!(GravityCurve::Nes.fall_delay(100), 1.0 / NES_FRAMES_PER_SECOND);
+    }
+
+    #[test]
+    fn guideline_gravity_follows_the_formula() {
+        assert_eq!(GravityCurve::Guideline.fall_delay(0), 1.0);
+        assert_eq!(GravityCurve::Guideline.fall_delay(1), 1.0);
+        assert!((GravityCurve::Guideline.fall_delay(2) - 0.793).abs() < 0.0001);
+        assert!((GravityCurve::Guideline.fall_delay(15) - 0.00706).abs() < 0.00001);
+        assert_eq!(GravityCurve::Guideline.fall_delay(30), GravityCurve::Guideline.fall_delay(GUIDELINE_MAX_LEVEL));
+    }
+
+    #[test]
+    fn tgm_gravity_slows_down_at_level_200_and_reaches_20g_at_level_500() {
+        assert_eq!(GravityCurve::Tgm.fall_delay(0), 64.0 / FRAMES_PER_SECOND);
+        assert!(GravityCurve::Tgm.fall_delay(200) > GravityCurve::Tgm.fall_delay(199));
+        assert_eq!(GravityCurve::Tgm.fall_delay(251), 1.0 / FRAMES_PER_SECOND);
+        assert_eq!(GravityCurve::Tgm.fall_delay(999), 1.0 / (20.0 * FRAMES_PER_SECOND));
+    }
+}
@@ -0,0 +1,1239 @@
+//! The rules of the game, without any dependency on bevy.
+//!
+//! A [`Game`] is advanced by calling [`Game::step`] with the current input and the time that has passed,
+//! which makes it possible to simulate games without a window (in tests, bots or CI).
+
+pub mod board;
+pub mod gravity;
+pub mod piece;
+pub mod randomizer;
+pub mod rotation;
+pub mod score;
+pub mod scoring;
+pub mod t_spin;
+pub mod timer;
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::consts::*;
+pub use board::{Board, BoardConfig};
+pub use gravity::GravityCurve;
+pub use piece::{Piece, PieceSet, Shape};
+pub use randomizer::Randomizer;
+pub use rotation::RotationSystem;
+pub use score::ScoreResource;
+pub use scoring::ScoringRules;
+use scoring::Placement;
+pub use t_spin::TSpin;
+use timer::Timer;
+
+/// The settings that a game is started with
+#[derive(Clone, Debug)]
+pub struct GameSettings {
+    pub starting_level: usize,
+    pub board: BoardConfig,
+    /// The pieces that the game is played with
+    pub pieces: PieceSet,
+    /// Decides how pieces look in each rotation and how they get kicked when rotating
+    pub rotation_system: Arc<dyn RotationSystem>,
+    /// Decides the order in which pieces are spawned
+    pub randomizer: Box<dyn Randomizer>,
+    /// Decides how much score placing pieces and clearing lines is worth
+    pub scoring: Arc<dyn ScoringRules>,
+    /// Decides how fast pieces fall on each level
+    pub gravity: GravityCurve,
+    /// How many of the upcoming pieces can be seen, between 1 and [`MAX_NEXT_QUEUE_LENGTH`]
+    pub next_queue_length: usize,
+    /// Can the current piece be put aside to be used later? (classic modes have no hold)
+    pub hold_enabled: bool,
+    /// What pressing up does
+    pub up_action: UpAction,
+    /// Can pieces be rotated by 180 degrees with a single key?
+    pub rotation_180_enabled: bool,
+    /// Initial rotation: does holding a rotation key while a piece spawns rotate it right away?
+    pub irs: bool,
+    /// Initial hold: does holding the hold key while a piece spawns hold it right away?
+    pub ihs: bool,
+    /// How long (in seconds) before the next piece spawns rotation, hold and hard drop presses are remembered for it.
+    /// Presses made during a line clear are kept until the entry delay starts. Buffered rotations and holds are only used with IRS and IHS
+    pub input_buffer: f32,
+    /// How the piece moves when keys are held
+    pub handling: HandlingSettings,
+    /// The pauses between pieces from each level on, sorted by level. Levels before the first entry have no pauses
+    pub delays: Vec<(usize, Delays)>,
+    /// How long (in seconds) a piece can stay on the ground before it gets placed
+    pub lock_delay: f32,
+    /// What restarts the lock delay
+    pub lock_reset: LockReset,
+    /// Is the position that the current piece would land in shown?
+    pub ghost_piece_enabled: bool,
+    /// The seed that every game is played with, so that the same piece sequence can be played again.
+    /// Without one, every game gets a new random seed
+    pub seed: Option<u64>
+}
+impl Default for GameSettings {
+    fn default() -> Self {
+        GameSettings {
+            starting_level: 1,
+            board: BoardConfig::default(),
+            pieces: PieceSet::standard(),
+            rotation_system: Arc::new(rotation::Srs),
+            randomizer: Box::new(randomizer::Bag::seven()),
+            scoring: Arc::new(scoring::Guideline),
+            gravity: GravityCurve::Guideline,
+            next_queue_length: 1,
+            hold_enabled: true,
+            up_action: UpAction::SonicDrop,
+            rotation_180_enabled: true,
+            irs: true,
+            ihs: true,
+            input_buffer: INPUT_BUFFER_TIME,
+            handling: HandlingSettings::default(),
+            delays: vec![(0, Delays::default())],
+            lock_delay: LOCK_DELAY,
+            lock_reset: LockReset::Moves(LOCK_RESET_MOVES),
+            ghost_piece_enabled: true,
+            seed: None
+        }
+    }
+}
+
+/// How the piece moves when keys are held, which every player likes differently
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HandlingSettings {
+    /// Delayed auto shift: how long (in seconds) a side movement key has to be held before the piece starts moving by itself
+    pub das: f32,
+    /// Auto repeat rate: the delay (in seconds) between movements once the piece moves by itself, 0 moves it to the wall instantly
+    pub arr: f32,
+    /// Does a new piece have to wait for the whole delayed auto shift again, even if the key was held before it spawned?
+    pub das_cut: bool,
+    /// How many times faster than gravity the piece falls when holding the down key, can be infinite
+    pub soft_drop_factor: f32
+}
+impl Default for HandlingSettings {
+    fn default() -> Self {
+        HandlingSettings { das: AUTO_SHIFT_DELAY, arr: AUTO_REPEAT_RATE, das_cut: false, soft_drop_factor: SOFT_DROP_FACTOR }
+    }
+}
+
+/// How long the pauses between placing a piece and the next piece appearing are
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Delays {
+    /// Entry delay (ARE): how long (in seconds) it takes for the next piece to appear
+    pub entry_delay: f32,
+    /// How long (in seconds) cleared lines stay on the board before they disappear, the entry delay comes after it
+    pub line_clear_delay: f32
+}
+impl Default for Delays {
+    fn default() -> Self {
+        Delays { entry_delay: ENTRY_DELAY, line_clear_delay: LINE_CLEAR_DELAY }
+    }
+}
+
+/// What the game is doing at the moment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// The current piece is in the air
+    Falling,
+    /// The current piece is on the ground, waiting for the lock delay to run out
+    Locking,
+    /// Full lines are about to disappear
+    LineClear,
+    /// Waiting for the next piece to appear
+    Entry
+}
+
+/// The ways that the up key can drop the current piece
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpAction {
+    /// Drop the piece to the floor without placing it, so it can still be moved
+    SonicDrop,
+    /// The same as the hard drop key
+    HardDrop
+}
+
+/// The ways that the lock delay of a piece on the ground can be restarted.
+/// Reaching a row that is lower than the piece has been before always restarts it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockReset {
+    /// Every move and rotation restarts the lock delay
+    Infinite,
+    /// Only the given amount of moves and rotations restart the lock delay, until the piece reaches a lower row
+    Moves(usize),
+    /// Only moving down restarts the lock delay
+    Step
+}
+
+/// Contains the upcoming pieces and the state of the randomizer that chooses the pieces
+#[derive(Clone, Debug)]
+pub struct PieceSelectionResource {
+    /// The pieces that will be spawned next, in order
+    pub next_pieces: VecDeque<Piece>,
+    pub randomizer: Box<dyn Randomizer>
+}
+
+/// The state of the player's input for a single step
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    /// Is the up key being held down?
+    pub up: bool,
+    /// Is the down key being held down?
+    pub down: bool,
+    /// Is the left key being held down?
+    pub left: bool,
+    /// Is the right key being held down?
+    pub right: bool,
+    /// Was the up key just pressed?
+    pub up_jp: bool,
+    /// Was the down key just pressed?
+    pub down_jp: bool,
+    /// Was the left key just pressed?
+    pub left_jp: bool,
+    /// Was the right key just pressed?
+    pub right_jp: bool,
+    /// Is the clockwise rotation key being held down?
+    pub rotate_clockwise: bool,
+    /// Is the anticlockwise rotation key being held down?
+    pub rotate_anticlockwise: bool,
+    /// Is the 180 degree rotation key being held down?
+    pub rotate_180: bool,
+    pub rotate_clockwise_jp: bool,
+    pub rotate_anticlockwise_jp: bool,
+    pub rotate_180_jp: bool,
+    /// Is the hold key being held down?
+    pub hold: bool,
+    /// Was the hold key just pressed?
+    pub hold_jp: bool,
+    /// Was the hard drop key just pressed?
+    pub hard_drop_jp: bool
+}
+
+/// Things that happened during a step, for the frontend to react to
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    /// A new piece of the given shape has appeared at the top of the board
+    PieceSpawned(Shape),
+    /// The current piece, of the given shape, has been put into the hold slot
+    PieceHeld(Shape),
+    /// The current piece was dropped straight down by the given amount of rows and gets placed right away
+    HardDropped(usize),
+    /// The current piece has become part of the tower
+    PiecePlaced,
+    /// The placed piece was a T-spin, which cleared the given amount of lines (which can be 0)
+    TSpin(TSpin, usize),
+    /// The given amount of lines (at least one) were cleared
+    LinesCleared(usize),
+    /// The given amount of lines were cleared and left the board completely empty
+    PerfectClear(usize),
+    /// The game has progressed to the given level
+    LevelUp(usize),
+    /// A new piece could not spawn because the tower was in the way (block out) or a piece was placed completely
+    /// above the visible part of the board (lock out), the game has been restarted. Contains the final score and the seed of the lost game
+    Loss { score: usize, seed: u64 }
+}
+
+/// A single game of tetris
+pub struct Game {
+    settings: GameSettings,
+    board: Board,
+    piece: Option<Piece>,
+    piece_selection: PieceSelectionResource,
+    /// The shape that was put aside with hold, if any
+    held_shape: Option<Shape>,
+    /// Holding is only allowed once per piece, until it gets placed
+    can_hold: bool,
+    score: ScoreResource,
+    level: usize, // TODO: would be cool if i added negative levels lmao
+    fall_timer: Timer,
+    /// The direction (-1 or 1) that the piece is being moved in by holding a side movement key, 0 if neither is held
+    shift_direction: i32,
+    /// Counts how long the side movement key has been held, until the piece starts moving by itself
+    das_timer: Timer,
+    /// Has the side movement key been held long enough for the piece to move by itself?
+    das_charged: bool,
+    /// Counts the time between movements once the piece moves by itself
+    arr_timer: Timer,
+    drop_input_timer: Timer,
+    /// Counts how long the current piece has been on the ground
+    lock_timer: Timer,
+    phase: Phase,
+    /// Counts down the line clear delay and the entry delay
+    delay_timer: Timer,
+    /// The keys that were pressed while there was no piece and how long ago (in seconds), which get used when the next piece spawns
+    input_buffer: Vec<(f32, InputState)>,
+    /// The lines that are about to disappear during the line clear delay
+    clearing_lines: Vec<usize>,
+    /// The seed of the current game
+    seed: u64,
+    /// All of the game's randomness comes from here, so the same seed always gives the same game
+    rng: StdRng
+}
+
+impl Game {
+    pub fn new(mut settings: GameSettings) -> Self {
+        settings.next_queue_length = settings.next_queue_length.clamp(1, MAX_NEXT_QUEUE_LENGTH);
+        let seed = settings.seed.unwrap_or_else(rand::random);
+
+        Game {
+            board: Board::new(settings.board.width, settings.board.height, vanish_rows(&settings)),
+            piece: None,
+            piece_selection: PieceSelectionResource { next_pieces: VecDeque::new(), randomizer: settings.randomizer.clone() },
+            held_shape: None,
+            can_hold: true,
+            score: ScoreResource::default(),
+            level: settings.starting_level,
+            fall_timer: Timer::from_seconds(settings.gravity.fall_delay(settings.starting_level)),
+            shift_direction: 0,
+            das_timer: Timer::from_seconds(settings.handling.das),
+            das_charged: false,
+            arr_timer: Timer::from_seconds(settings.handling.arr),
+            drop_input_timer: Timer::from_seconds(settings.gravity.fall_delay(settings.starting_level) / settings.handling.soft_drop_factor),
+            lock_timer: Timer::from_seconds(settings.lock_delay),
+            // the first piece appears right away
+            phase: Phase::Entry,
+            delay_timer: Timer::from_seconds(0.0),
+            input_buffer: vec![],
+            clearing_lines: vec![],
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            settings
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn board_config(&self) -> &BoardConfig {
+        &self.settings.board
+    }
+
+    /// The piece that is currently being controlled, if there is one
+    pub fn piece(&self) -> Option<&Piece> {
+        self.piece.as_ref()
+    }
+
+    pub fn rotation_system(&self) -> &dyn RotationSystem {
+        self.settings.rotation_system.as_ref()
+    }
+
+    pub fn pieces(&self) -> &PieceSet {
+        &self.settings.pieces
+    }
+
+    /// Gets the board coordinates of every segment of a piece
+    pub fn segment_positions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        piece.segment_positions(self.rotation_system(), self.pieces())
+    }
+
+    pub fn piece_selection(&self) -> &PieceSelectionResource {
+        &self.piece_selection
+    }
+
+    /// The shape in the hold slot, if there is one
+    pub fn held_shape(&self) -> Option<Shape> {
+        self.held_shape
+    }
+
+    pub fn hold_enabled(&self) -> bool {
+        self.settings.hold_enabled
+    }
+
+    /// Can the current piece be held? (false after holding until the piece is placed)
+    pub fn can_hold(&self) -> bool {
+        self.can_hold
+    }
+
+    /// How many upcoming pieces are shown
+    pub fn next_queue_length(&self) -> usize {
+        self.settings.next_queue_length
+    }
+
+    pub fn score(&self) -> &ScoreResource {
+        &self.score
+    }
+
+    /// The grade that the current score has earned, if the scoring rules have grades
+    pub fn grade(&self) -> Option<&'static str> {
+        self.settings.scoring.grade(self.score.score())
+    }
+
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// How far the game is into the line clear delay or the entry delay, from 0 to 1
+    pub fn delay_progress(&self) -> f32 {
+        self.delay_timer.fraction()
+    }
+
+    /// The lines (from the bottom) that are about to disappear, during the line clear delay
+    pub fn clearing_lines(&self) -> &[usize] {
+        &self.clearing_lines
+    }
+
+    /// The pauses between pieces on the current level
+    pub fn delays(&self) -> Delays {
+        self.settings.delays.iter().rev()
+            .find(|(level, _)| *level <= self.level)
+            .map(|&(_, delays)| delays)
+            .unwrap_or(Delays { entry_delay: 0.0, line_clear_delay: 0.0 })
+    }
+
+    /// The seed that the current game's randomness comes from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The current piece moved to where it would land if it was hard dropped, if the ghost piece is enabled
+    pub fn ghost_piece(&self) -> Option<Piece> {
+        if !self.settings.ghost_piece_enabled {
+            return None;
+        }
+
+        let piece = self.piece.as_ref()?;
+        Some(Piece { y: self.drop_position(piece), ..piece.clone() })
+    }
+
+    pub fn ghost_piece_enabled(&self) -> bool {
+        self.settings.ghost_piece_enabled
+    }
+
+    pub fn set_ghost_piece_enabled(&mut self, enabled: bool) {
+        self.settings.ghost_piece_enabled = enabled;
+    }
+
+    /// Advances the game by `delta` seconds with the given input, returns everything that happened
+    pub fn step(&mut self, input: &InputState, delta: f32) -> Vec<GameEvent> {
+        let mut events = vec![];
+
+        // presses during the delays between pieces are not lost
+        if self.piece.is_none() {
+            self.buffer_input(input, delta);
+        }
+
+        // once a delay has ended the next phase starts counting from 0, in the same step
+        let mut delay_delta = delta;
+        let mut spawned = false;
+        if self.phase == Phase::LineClear {
+            self.delay_timer.tick(delay_delta);
+
+            if self.delay_timer.just_finished() {
+                self.board.clear_lines();
+                self.clearing_lines.clear();
+                self.start_delay(Phase::Entry, self.delays().entry_delay);
+                delay_delta = 0.0;
+            }
+        }
+        if self.phase == Phase::Entry {
+            self.delay_timer.tick(delay_delta);
+
+            if self.delay_timer.just_finished() {
+                self.spawn_piece(&mut events);
+                spawned = true;
+            }
+        }
+        let input = &if spawned { self.initial_input(input) } else { input.clone() };
+
+        if input.hold_jp {
+            self.hold_piece(&mut events);
+        }
+
+        // reset the drop input timer (faster than side to side movement)
+        if input.down_jp {
+            self.drop_input_timer.reset();
+        }
+        // soft drop speed depends on how fast the piece is falling anyway
+        self.drop_input_timer.set_duration(self.fall_timer.duration() / self.settings.handling.soft_drop_factor);
+
+        self.fall_timer.tick(delta);
+        self.drop_input_timer.tick(delta);
+        let shift = self.auto_shift(input, delta);
+
+        if self.move_piece(input, shift, delta) {
+            self.place_piece(0, &mut events);
+        }
+        else if input.hard_drop_jp || (input.up_jp && self.settings.up_action == UpAction::HardDrop) {
+            self.hard_drop(&mut events);
+        }
+
+        events
+    }
+
+    /// Remembers the keys that were pressed, to be used once there is a piece again, and forgets the ones that were pressed too long
+    /// before it spawns
+    fn buffer_input(&mut self, input: &InputState, delta: f32) {
+        // presses only start getting old once the entry delay has started, so none are lost to long line clears
+        if self.phase == Phase::Entry {
+            let window = self.settings.input_buffer;
+            for (age, _) in self.input_buffer.iter_mut() {
+                *age += delta;
+            }
+            self.input_buffer.retain(|(age, _)| *age <= window);
+        }
+
+        if input.rotate_clockwise_jp || input.rotate_anticlockwise_jp || input.rotate_180_jp || input.hold_jp || input.hard_drop_jp {
+            self.input_buffer.push((0.0, InputState {
+                rotate_clockwise_jp: input.rotate_clockwise_jp,
+                rotate_anticlockwise_jp: input.rotate_anticlockwise_jp,
+                rotate_180_jp: input.rotate_180_jp,
+                hold_jp: input.hold_jp,
+                hard_drop_jp: input.hard_drop_jp,
+                ..Default::default()
+            }));
+        }
+    }
+
+    /// Adds the buffered presses and the initial rotation and hold to the input of the step that a piece spawned in
+    fn initial_input(&mut self, input: &InputState) -> InputState {
+        let mut input = input.clone();
+        let (irs, ihs) = (self.settings.irs, self.settings.ihs);
+
+        // buffered rotations and holds are initial rotations and holds too, so they need IRS and IHS
+        for (_, buffered) in std::mem::take(&mut self.input_buffer) {
+            input.rotate_clockwise_jp |= irs && buffered.rotate_clockwise_jp;
+            input.rotate_anticlockwise_jp |= irs && buffered.rotate_anticlockwise_jp;
+            input.rotate_180_jp |= irs && buffered.rotate_180_jp;
+            input.hold_jp |= ihs && buffered.hold_jp;
+            input.hard_drop_jp |= buffered.hard_drop_jp;
+        }
+        input.rotate_clockwise_jp |= irs && input.rotate_clockwise;
+        input.rotate_anticlockwise_jp |= irs && input.rotate_anticlockwise;
+        input.rotate_180_jp |= irs && input.rotate_180;
+        input.hold_jp |= ihs && input.hold;
+
+        input
+    }
+
+    /// Spawns the first piece of the next queue and fills the queue back up
+    fn spawn_piece(&mut self, events: &mut Vec<GameEvent>) {
+        let next_piece = match self.piece_selection.next_pieces.pop_front() {
+            Some(next_piece) => next_piece,
+            None => self.random_piece()
+        };
+        while self.piece_selection.next_pieces.len() < self.settings.next_queue_length {
+            let piece = self.random_piece();
+            self.piece_selection.next_pieces.push_back(piece);
+        }
+
+        self.start_piece(next_piece, events);
+    }
+
+    /// Makes the given piece the current one, unless the tower is in the way
+    fn start_piece(&mut self, mut piece: Piece, events: &mut Vec<GameEvent>) {
+        // block out
+        if !self.piece_fits(&piece, piece.x, piece.y, piece.rotation) {
+            self.lose(events);
+            return;
+        }
+
+        // the piece moves down right away if it can, so that it can be seen
+        if self.piece_fits(&piece, piece.x, piece.y - 1, piece.rotation) {
+            piece.y -= 1;
+            piece.lowest_y = piece.y;
+        }
+
+        events.push(GameEvent::PieceSpawned(piece.shape));
+        self.piece = Some(piece);
+        self.lock_timer.reset();
+        self.fall_timer.reset();
+        self.phase = Phase::Falling;
+
+        if self.settings.handling.das_cut {
+            self.das_timer.reset();
+            self.das_charged = false;
+        }
+    }
+
+    /// Works out the direction and the amount of columns that the side movement keys move the piece by this step
+    fn auto_shift(&mut self, input: &InputState, delta: f32) -> (i32, usize) {
+        let mut columns = 0;
+
+        // the key that was pressed last decides the direction when both are held
+        if input.left_jp || input.right_jp {
+            self.shift_direction = if input.left_jp { -1 } else { 1 };
+            self.das_timer.reset();
+            self.das_charged = false;
+            columns += 1;
+        }
+        // if the key is let go of while the other one is still held, the piece starts moving the other way
+        else if (self.shift_direction == -1 && !input.left) || (self.shift_direction == 1 && !input.right) {
+            self.shift_direction = if input.left { -1 } else if input.right { 1 } else { 0 };
+            self.das_timer.reset();
+            self.das_charged = false;
+        }
+
+        if self.shift_direction == 0 {
+            return (0, 0);
+        }
+
+        if !self.das_charged {
+            self.das_timer.tick(delta);
+
+            if self.das_timer.just_finished() {
+                self.das_charged = true;
+                self.arr_timer.reset();
+                columns += 1;
+            }
+        }
+        else {
+            self.arr_timer.tick(delta);
+            columns += self.arr_timer.times_finished();
+        }
+
+        // an auto repeat rate of 0 moves the piece all the way to the wall
+        if self.das_charged && self.settings.handling.arr <= 0.0 {
+            columns = usize::MAX;
+        }
+
+        (self.shift_direction, columns)
+    }
+
+    /// Swaps the current piece with the one in the hold slot, or with the next piece if the slot is empty
+    fn hold_piece(&mut self, events: &mut Vec<GameEvent>) {
+        if !self.settings.hold_enabled || !self.can_hold {
+            return;
+        }
+        let piece = match self.piece.take() {
+            Some(piece) => piece,
+            None => return
+        };
+
+        events.push(GameEvent::PieceHeld(piece.shape));
+        self.can_hold = false;
+        self.fall_timer.reset();
+
+        match self.held_shape.replace(piece.shape) {
+            Some(shape) => {
+                let piece = self.spawned_piece(shape);
+                self.start_piece(piece, events);
+            },
+            None => self.spawn_piece(events)
+        }
+    }
+
+    /// Creates a piece with a shape chosen by the randomizer at the spawn position
+    fn random_piece(&mut self) -> Piece {
+        let shape = self.piece_selection.randomizer.next_shape(&mut self.rng, &self.settings.pieces);
+        self.spawned_piece(shape)
+    }
+
+    /// Creates a piece of the given shape in the rotation system's spawn state, with its lowest segments on the spawn row
+    fn spawned_piece(&self, shape: Shape) -> Piece {
+        let lowest_offset = self.rotation_system().get_rotation_state(self.pieces(), shape, 0).iter().map(|&(_, y)| y).min().unwrap_or(0);
+        Piece::new(shape, self.settings.board.spawn_x, self.settings.board.spawn_y - lowest_offset, 0)
+    }
+
+    /// Checks whether every segment of the piece would be on a free cell of the board at the given position and rotation
+    fn piece_fits(&self, piece: &Piece, x: i32, y: i32, rotation: usize) -> bool {
+        piece.fits(&self.board, self.rotation_system(), self.pieces(), x, y, rotation)
+    }
+
+    /// The lowest Y position that the piece can be dropped to from where it is
+    pub fn drop_position(&self, piece: &Piece) -> i32 {
+        let mut y = piece.y;
+        while self.piece_fits(piece, piece.x, y - 1, piece.rotation) {
+            y -= 1;
+        }
+        y
+    }
+
+    /// Moves the current piece as far down as it can go, rewards the rows it was dropped by and places it
+    fn hard_drop(&mut self, events: &mut Vec<GameEvent>) {
+        let piece = match self.piece.as_ref() {
+            Some(piece) => piece,
+            None => return
+        };
+
+        let y = self.drop_position(piece);
+        let rows = (piece.y - y) as usize;
+        if let Some(piece) = self.piece.as_mut().filter(|_| rows > 0) {
+            piece.y = y;
+            piece.last_kick = None;
+        }
+
+        events.push(GameEvent::HardDropped(rows));
+        self.place_piece(rows, events);
+    }
+
+    /// Handles the piece being moved by the player and gravity, returns true if the piece should be placed
+    fn move_piece(&mut self, input: &InputState, (movement_x, columns): (i32, usize), delta: f32) -> bool {
+        let mut piece = match self.piece.take() {
+            Some(piece) => piece,
+            None => return false
+        };
+
+        // how many rows the piece is being dropped manually this step, an infinite soft drop goes all the way down
+        let dropped_rows = if !input.down && !input.down_jp {
+            0
+        }
+        else if self.settings.handling.soft_drop_factor.is_infinite() {
+            usize::MAX
+        }
+        else {
+            (input.down_jp as usize).max(self.drop_input_timer.times_finished())
+        };
+        // apply "gravity", which can be more than one row in a step when pieces fall fast
+        let gravity_rows = if dropped_rows == 0 { self.fall_timer.times_finished() } else { 0 };
+
+        // whether the piece was successfully moved or rotated by the player this step
+        let mut moved = false;
+
+        // rotation and rotation collision / edge checking
+        let rotate_180 = input.rotate_180_jp && self.settings.rotation_180_enabled;
+        if input.rotate_clockwise_jp || input.rotate_anticlockwise_jp || rotate_180 {
+            let desired_rotation = if input.rotate_clockwise_jp { (piece.rotation + 1) % 4 } else if input.rotate_anticlockwise_jp { (piece.rotation + 3) % 4 } else { (piece.rotation + 2) % 4 };
+
+            // the rotation system decides whether (and where) the piece can be rotated
+            if let Some(kick) = rotation::rotate(self.settings.rotation_system.as_ref(), &self.board, &self.settings.pieces, &mut piece, desired_rotation) {
+                piece.last_kick = Some(kick);
+                moved = true;
+            }
+        }
+
+        // side movement into the edge or the tower is blocked
+        for _ in 0..columns {
+            if !self.piece_fits(&piece, piece.x + movement_x, piece.y, piece.rotation) {
+                break;
+            }
+            piece.x += movement_x;
+            piece.last_kick = None;
+            moved = true;
+        }
+
+        // anything that moves the piece down means that it was not rotated into its spot
+        let y_before_drop = piece.y;
+
+        // sonic drop moves the piece to the floor, but it only gets placed once the lock delay runs out
+        if input.up_jp && self.settings.up_action == UpAction::SonicDrop {
+            let y = self.drop_position(&piece);
+            piece.dropped_pixels += (piece.y - y) as usize;
+            piece.y = y;
+        }
+
+        // falling is blocked by the bottom of the board and the tower
+        for _ in 0..dropped_rows {
+            if !self.piece_fits(&piece, piece.x, piece.y - 1, piece.rotation) {
+                break;
+            }
+            piece.y -= 1;
+            piece.dropped_pixels += 1;
+        }
+        for _ in 0..gravity_rows {
+            if !self.piece_fits(&piece, piece.x, piece.y - 1, piece.rotation) {
+                break;
+            }
+            piece.y -= 1;
+        }
+        if piece.y != y_before_drop {
+            piece.last_kick = None;
+        }
+
+        // reaching a new lowest row always restarts the lock delay, moving and rotating only does if the lock reset rule allows it
+        if piece.y < piece.lowest_y {
+            piece.lowest_y = piece.y;
+            piece.lock_resets = 0;
+            self.lock_timer.reset();
+        }
+        else if moved {
+            let reset = match self.settings.lock_reset {
+                LockReset::Infinite => true,
+                LockReset::Moves(moves) => piece.lock_resets < moves,
+                LockReset::Step => false
+            };
+
+            if reset {
+                piece.lock_resets += 1;
+                self.lock_timer.reset();
+            }
+        }
+
+        // the lock delay only runs while the piece is on the bottom of the board or on top of the tower
+        let place = if self.piece_fits(&piece, piece.x, piece.y - 1, piece.rotation) {
+            self.phase = Phase::Falling;
+            false
+        }
+        else {
+            self.phase = Phase::Locking;
+            self.lock_timer.tick(delta);
+            self.lock_timer.just_finished()
+        };
+
+        self.piece = Some(piece);
+        place
+    }
+
+    /// Makes the current piece part of the tower, then checks for lines and losing
+    fn place_piece(&mut self, hard_dropped_rows: usize, events: &mut Vec<GameEvent>) {
+        let piece = match self.piece.take() {
+            Some(piece) => piece,
+            None => return
+        };
+
+        let t_spin = t_spin::detect_t_spin(&self.board, self.rotation_system(), self.pieces(), &piece);
+
+        for (x, y) in self.segment_positions(&piece) {
+            self.board.set(x, y, Some(piece.shape));
+        }
+
+        self.fall_timer.reset();
+        self.drop_input_timer.reset();
+        self.can_hold = true;
+
+        events.push(GameEvent::PiecePlaced);
+
+        // lock out
+        if self.segment_positions(&piece).iter().all(|&(_, y)| y >= self.board.height() as i32) {
+            self.lose(events);
+            return;
+        }
+
+        self.check_lines(Placement { t_spin, level: self.level, soft_dropped_rows: piece.dropped_pixels, hard_dropped_rows, ..Default::default() }, events);
+    }
+
+    /// Ends the game and starts a new one
+    fn lose(&mut self, events: &mut Vec<GameEvent>) {
+        events.push(GameEvent::Loss { score: self.score.score(), seed: self.seed });
+        self.restart();
+    }
+
+    /// Starts waiting for the line clear delay or the entry delay
+    fn start_delay(&mut self, phase: Phase, duration: f32) {
+        self.phase = phase;
+        self.delay_timer = Timer::from_seconds(duration);
+    }
+
+    /// Rewards the placed piece and its full lines (and T-spins) and starts the delay before they get cleared, levels up if enough lines have been cleared
+    fn check_lines(&mut self, mut placement: Placement, events: &mut Vec<GameEvent>) {
+        // the full lines disappear after the line clear delay (also keep count of them, for singles, doubles, triples, tetrises)
+        self.clearing_lines = self.board.full_lines();
+        let cleared_lines = self.clearing_lines.len();
+        // nothing but the cleared lines is left of the tower
+        let perfect_clear = cleared_lines > 0 && self.board.is_empty_without_full_lines();
+
+        // reward score for the piece and its lines (also increases the line counter on the score), the scoring rules decide what everything is worth
+        placement.lines = cleared_lines;
+        placement.perfect_clear = perfect_clear;
+        self.score.reward_placement(self.settings.scoring.as_ref(), &placement);
+
+        // T-spins are announced even if they don't clear any lines
+        if let Some(t_spin) = placement.t_spin {
+            events.push(GameEvent::TSpin(t_spin, cleared_lines));
+        }
+
+        if cleared_lines == 0 {
+            self.start_delay(Phase::Entry, self.delays().entry_delay);
+            return;
+        }
+        self.start_delay(Phase::LineClear, self.delays().line_clear_delay);
+        events.push(GameEvent::LinesCleared(cleared_lines));
+        if perfect_clear {
+            events.push(GameEvent::PerfectClear(cleared_lines));
+        }
+
+        // check if enough lines have been cleared to progress to the next level (on the starting level, more lines need to be cleared)
+        let starting_level = self.settings.starting_level;
+        if self.score.lines() >= (starting_level + 1) * LINES_PER_LEVEL + (self.level - starting_level) * LINES_PER_LEVEL {
+            self.level_up(events);
+        }
+    }
+
+    fn level_up(&mut self, events: &mut Vec<GameEvent>) {
+        self.level += 1;
+
+        // make pieces fall faster now
+        self.fall_timer.set_duration(self.settings.gravity.fall_delay(self.level));
+
+        events.push(GameEvent::LevelUp(self.level));
+    }
+
+    /// Starts playing with another piece set, which restarts the game
+    pub fn set_piece_set(&mut self, pieces: PieceSet) {
+        self.settings.pieces = pieces;
+        // taller pieces need more room above the board
+        self.board = Board::new(self.settings.board.width, self.settings.board.height, vanish_rows(&self.settings));
+        self.restart();
+    }
+
+    /// Erases the tower and the current piece, resets the score and level and starts over with a new seed (or the same one if it was set in the settings)
+    pub fn restart(&mut self) {
+        self.seed = self.settings.seed.unwrap_or_else(rand::random);
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.piece_selection.next_pieces.clear();
+        self.piece_selection.randomizer.reset();
+
+        self.board.clear();
+        self.piece = None;
+        self.clearing_lines.clear();
+        self.input_buffer.clear();
+        self.start_delay(Phase::Entry, 0.0);
+        self.held_shape = None;
+        self.can_hold = true;
+        self.score.reset();
+        // reset level to starting level for quick restart ig
+        self.level = self.settings.starting_level;
+        self.fall_timer.set_duration(self.settings.gravity.fall_delay(self.level));
+    }
+}
+
+/// How many hidden rows the board needs above it: at least 2 and the amount in the board config,
+/// and enough for every piece of the piece set to spawn completely inside the board
+fn vanish_rows(settings: &GameSettings) -> usize {
+    let tallest_spawn_state = (0..settings.pieces.len())
+        .map(|index| {
+            let offsets = settings.rotation_system.get_rotation_state(&settings.pieces, Shape(index), 0);
+            let lowest = offsets.iter().map(|&(_, y)| y).min().unwrap_or(0);
+            let highest = offsets.iter().map(|&(_, y)| y).max().unwrap_or(0);
+            highest - lowest + 1
+        })
+        .max()
+        .unwrap_or(0);
+    let rows_above_board = settings.board.spawn_y + tallest_spawn_state - settings.board.height as i32;
+
+    settings.board.vanish_rows.max(2).max(rows_above_board.max(0) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays a game with the given settings and a fixed, varied input sequence, returns every event
+    fn play(settings: GameSettings, steps: usize) -> Vec<GameEvent> {
+        let mut game = Game::new(settings);
+        let mut events = vec![];
+
+        for step in 0..steps {
+            let input = InputState {
+                left: step % 40 < 8,
+                right: step % 40 >= 30,
+                down: step % 7 == 0,
+                rotate_clockwise_jp: step % 11 == 0,
+                rotate_anticlockwise_jp: step % 17 == 0,
+                hold_jp: step % 53 == 0,
+                hard_drop_jp: step % 20 == 19,
+                ..Default::default()
+            };
+            events.extend(game.step(&input, 1.0 / 60.0));
+        }
+
+        events
+    }
+
+    #[test]
+    fn same_seed_and_input_give_the_same_game() {
+        let settings = || GameSettings { seed: Some(42), next_queue_length: 5, ..Default::default() };
+        let events = play(settings(), 3000);
+
+        assert!(events.iter().filter(|event| matches!(event, GameEvent::PiecePlaced)).count() > 50);
+        assert_eq!(events, play(settings(), 3000));
+    }
+
+    #[test]
+    fn the_first_seven_pieces_are_one_of_each_shape() {
+        let mut game = Game::new(GameSettings { seed: Some(3), next_queue_length: 6, ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+
+        let mut shapes: Vec<usize> = game.piece().into_iter().chain(game.piece_selection().next_pieces.iter()).map(|piece| piece.shape.0).collect();
+        shapes.sort_unstable();
+        assert_eq!(shapes, (0..7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn different_seeds_give_different_pieces() {
+        let spawned = |seed| play(GameSettings { seed: Some(seed), ..Default::default() }, 1000).into_iter()
+            .filter(|event| matches!(event, GameEvent::PieceSpawned(_)))
+            .collect::<Vec<_>>();
+
+        assert_ne!(spawned(1), spawned(2));
+    }
+
+    #[test]
+    fn restarting_with_a_seed_repeats_the_piece_sequence() {
+        let mut game = Game::new(GameSettings { seed: Some(7), next_queue_length: 5, ..Default::default() });
+        let queue = |game: &Game| game.piece_selection().next_pieces.iter().map(|piece| piece.shape).collect::<Vec<_>>();
+        game.step(&InputState::default(), 0.0);
+        let first = queue(&game);
+
+        game.restart();
+        game.step(&InputState::default(), 0.0);
+        assert_eq!(game.seed(), 7);
+        assert_eq!(queue(&game), first);
+    }
+
+    /// Drops the first piece to the floor and keeps moving it left and right, returns how long it took to lock (if it did in 10 seconds)
+    fn lock_time(lock_reset: LockReset) -> Option<f32> {
+        let mut game = Game::new(GameSettings { seed: Some(0), lock_reset, ..Default::default() });
+        game.step(&InputState { up_jp: true, ..Default::default() }, 0.0);
+
+        for step in 1..=200 {
+            let input = InputState { left_jp: step % 2 == 0, right_jp: step % 2 == 1, ..Default::default() };
+            if game.step(&input, 0.05).contains(&GameEvent::PiecePlaced) {
+                return Some(step as f32 * 0.05);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn infinite_lock_reset_never_locks_a_moving_piece() {
+        assert_eq!(lock_time(LockReset::Infinite), None);
+    }
+
+    #[test]
+    fn step_lock_reset_locks_after_the_lock_delay() {
+        let time = lock_time(LockReset::Step).unwrap();
+        assert!((time - LOCK_DELAY).abs() < 0.06, "locked after {}", time);
+    }
+
+    #[test]
+    fn move_lock_reset_locks_once_the_moves_run_out() {
+        let time = lock_time(LockReset::Moves(LOCK_RESET_MOVES)).unwrap();
+        assert!(time > LOCK_RESET_MOVES as f32 * 0.05 && time < LOCK_RESET_MOVES as f32 * 0.05 + LOCK_DELAY + 0.06, "locked after {}", time);
+    }
+
+    /// Holds the left key for the given amount of 1/64 second steps, returns how many columns the first piece had moved after each step
+    fn shifted_columns(handling: HandlingSettings, steps: usize) -> Vec<i32> {
+        let mut game = Game::new(GameSettings { seed: Some(0), handling, ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+        let spawn_x = game.piece().unwrap().x;
+
+        (0..steps).map(|step| {
+            game.step(&InputState { left: true, left_jp: step == 0, ..Default::default() }, 1.0 / 64.0);
+            spawn_x - game.piece().unwrap().x
+        }).collect()
+    }
+
+    #[test]
+    fn held_keys_shift_once_then_repeat_after_the_auto_shift_delay() {
+        let columns = shifted_columns(HandlingSettings { das: 8.0 / 64.0, arr: 2.0 / 64.0, ..Default::default() }, 11);
+
+        // the press moves the piece right away, then nothing happens until the delay has passed
+        assert_eq!(columns[0], 1);
+        assert_eq!(columns[6], 1);
+        assert_eq!(columns[7], 2);
+        // and from then on it moves every other step
+        assert_eq!(columns[8], 2);
+        assert_eq!(columns[9], 3);
+        assert_eq!(columns[10], 3);
+    }
+
+    #[test]
+    fn zero_auto_repeat_rate_shifts_to_the_wall() {
+        let columns = shifted_columns(HandlingSettings { das: 8.0 / 64.0, arr: 0.0, ..Default::default() }, 12);
+
+        assert_eq!(columns[6], 1);
+        assert!(columns[7] > 2);
+        assert_eq!(columns[7], columns[11]);
+    }
+
+    /// Keeps holding the left key after charging the auto shift and hard dropping the first piece,
+    /// returns how many columns the next piece moved in its first few steps
+    fn shift_after_spawn(das_cut: bool) -> i32 {
+        let handling = HandlingSettings { das: 8.0 / 64.0, arr: 0.0, das_cut, ..Default::default() };
+        let mut game = Game::new(GameSettings { seed: Some(0), handling, ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+        game.step(&InputState { left: true, left_jp: true, ..Default::default() }, 0.25);
+        game.step(&InputState { left: true, hard_drop_jp: true, ..Default::default() }, 1.0 / 64.0);
+
+        let mut spawned_steps = 0;
+        while spawned_steps < 6 {
+            game.step(&InputState { left: true, ..Default::default() }, 1.0 / 64.0);
+            spawned_steps += game.piece().is_some() as usize;
+        }
+        game.board_config().spawn_x - game.piece().unwrap().x
+    }
+
+    #[test]
+    fn a_charged_auto_shift_carries_over_to_the_next_piece() {
+        assert!(shift_after_spawn(false) > 2);
+    }
+
+    #[test]
+    fn das_cut_makes_the_next_piece_wait_for_the_auto_shift_delay() {
+        assert_eq!(shift_after_spawn(true), 0);
+    }
+
+    /// Builds a tower of the given height with a gap in the first column, so that no lines are full
+    fn game_with_tower(height: usize) -> Game {
+        let mut game = Game::new(GameSettings { seed: Some(0), ..Default::default() });
+        for y in 0..height as i32 {
+            for x in 1..game.board.width() as i32 {
+                game.board.set(x, y, Some(Shape(0)));
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn pieces_that_cannot_spawn_block_out() {
+        let mut game = game_with_tower(BOARD_HEIGHT + 1);
+        assert!(game.step(&InputState::default(), 0.0).iter().any(|event| matches!(event, GameEvent::Loss { .. })));
+    }
+
+    #[test]
+    fn pieces_placed_above_the_visible_board_lock_out() {
+        let mut game = game_with_tower(BOARD_HEIGHT);
+        assert!(!game.step(&InputState::default(), 0.0).iter().any(|event| matches!(event, GameEvent::Loss { .. })));
+        assert!(game.step(&InputState { hard_drop_jp: true, ..Default::default() }, 0.0).iter().any(|event| matches!(event, GameEvent::Loss { .. })));
+    }
+
+    #[test]
+    fn pieces_placed_partly_on_the_visible_board_do_not_lock_out() {
+        let mut game = game_with_tower(BOARD_HEIGHT - 1);
+        game.step(&InputState::default(), 0.0);
+        assert!(!game.step(&InputState { hard_drop_jp: true, ..Default::default() }, 0.0).iter().any(|event| matches!(event, GameEvent::Loss { .. })));
+    }
+
+    /// Hard drops the first piece, returns how many 1/64 second steps it took for the next piece to spawn
+    fn steps_until_next_piece(delays: Delays, clear_line: bool) -> usize {
+        let mut game = Game::new(GameSettings { seed: Some(0), delays: vec![(0, delays)], ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+        // a full row anywhere on the board gets cleared along with the rows that the piece completes
+        if clear_line {
+            for x in 0..game.board.width() as i32 {
+                game.board.set(x, 0, Some(Shape(0)));
+            }
+        }
+        game.step(&InputState { hard_drop_jp: true, ..Default::default() }, 0.0);
+
+        (1..).find(|_| game.step(&InputState::default(), 1.0 / 64.0).iter().any(|event| matches!(event, GameEvent::PieceSpawned(_)))).unwrap()
+    }
+
+    #[test]
+    fn the_next_piece_spawns_after_the_entry_delay() {
+        assert_eq!(steps_until_next_piece(Delays { entry_delay: 4.0 / 64.0, line_clear_delay: 8.0 / 64.0 }, false), 4);
+    }
+
+    #[test]
+    fn clearing_lines_adds_the_line_clear_delay() {
+        assert_eq!(steps_until_next_piece(Delays { entry_delay: 4.0 / 64.0, line_clear_delay: 8.0 / 64.0 }, true), 12);
+        assert_eq!(steps_until_next_piece(Delays { entry_delay: 0.0, line_clear_delay: 8.0 / 64.0 }, true), 8);
+    }
+
+    #[test]
+    fn delays_depend_on_the_level() {
+        let slow = Delays { entry_delay: 0.5, line_clear_delay: 0.5 };
+        let fast = Delays { entry_delay: 0.1, line_clear_delay: 0.2 };
+        let delays = |starting_level| Game::new(GameSettings { starting_level, delays: vec![(1, slow), (10, fast)], ..Default::default() }).delays();
+
+        assert_eq!(delays(0), Delays { entry_delay: 0.0, line_clear_delay: 0.0 });
+        assert_eq!(delays(1), slow);
+        assert_eq!(delays(9), slow);
+        assert_eq!(delays(15), fast);
+    }
+
+    /// Steps the game in 1/64 second steps without any input until the next piece spawns, returns the events of that step
+    fn step_until_spawned(game: &mut Game) -> Vec<GameEvent> {
+        loop {
+            let events = game.step(&InputState::default(), 1.0 / 64.0);
+            if events.iter().any(|event| matches!(event, GameEvent::PieceSpawned(_))) {
+                return events;
+            }
+        }
+    }
+
+    /// Starts a game on a board that is 4 columns wide with only O pieces, and hard drops two of them side by side,
+    /// which clears the two bottom lines. Returns the game and the events of the second drop
+    fn clear_two_lines_with_o_pieces() -> (Game, Vec<GameEvent>) {
+        let pieces = PieceSet::from_ron(br#"(pieces: [(name: "O", color: (0.0, 1.0, 1.0), states: [[(0, 0), (1, 0), (0, 1), (1, 1)]], kicks: None)])"#).unwrap();
+        let mut game = Game::new(GameSettings { board: BoardConfig::new(4, 20), pieces, seed: Some(0), ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+
+        game.step(&InputState { left_jp: true, hard_drop_jp: true, ..Default::default() }, 0.0);
+        step_until_spawned(&mut game);
+        let events = game.step(&InputState { right_jp: true, hard_drop_jp: true, ..Default::default() }, 0.0);
+        (game, events)
+    }
+
+    #[test]
+    fn presses_during_a_line_clear_are_used_by_the_next_piece() {
+        let (mut game, _) = clear_two_lines_with_o_pieces();
+        assert_eq!(game.phase(), Phase::LineClear);
+
+        game.step(&InputState { hard_drop_jp: true, ..Default::default() }, 0.05);
+        assert!(step_until_spawned(&mut game).iter().any(|event| matches!(event, GameEvent::HardDropped(_))));
+    }
+
+    #[test]
+    fn presses_made_too_long_before_the_next_piece_are_forgotten() {
+        let delays = Delays { entry_delay: 0.5, line_clear_delay: 0.0 };
+        let mut game = Game::new(GameSettings { delays: vec![(0, delays)], input_buffer: 0.15, seed: Some(0), ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+        game.step(&InputState { hard_drop_jp: true, ..Default::default() }, 0.0);
+
+        game.step(&InputState { hard_drop_jp: true, ..Default::default() }, 0.1);
+        assert!(!step_until_spawned(&mut game).iter().any(|event| matches!(event, GameEvent::HardDropped(_))));
+    }
+
+    /// Holds the clockwise rotation and hold keys while the second piece spawns, returns its rotation and whether it was held
+    fn spawn_with_keys_held(irs: bool, ihs: bool) -> (usize, bool) {
+        let mut game = Game::new(GameSettings { irs, ihs, seed: Some(0), ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+        game.step(&InputState { hard_drop_jp: true, ..Default::default() }, 0.0);
+
+        loop {
+            let events = game.step(&InputState { rotate_clockwise: true, hold: true, ..Default::default() }, 1.0 / 64.0);
+            if let Some(piece) = game.piece() {
+                return (piece.rotation, events.iter().any(|event| matches!(event, GameEvent::PieceHeld(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn initial_rotation_and_hold_only_happen_when_enabled() {
+        assert_eq!(spawn_with_keys_held(true, true), (1, true));
+        assert_eq!(spawn_with_keys_held(true, false), (1, false));
+        assert_eq!(spawn_with_keys_held(false, true), (0, true));
+        assert_eq!(spawn_with_keys_held(false, false), (0, false));
+    }
+
+    /// Presses the 180 degree rotation key once the first piece has spawned, returns the piece's rotation afterwards
+    fn rotation_after_half_turn(rotation_180_enabled: bool) -> usize {
+        let mut game = Game::new(GameSettings { rotation_180_enabled, seed: Some(0), ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+        game.step(&InputState { rotate_180_jp: true, ..Default::default() }, 0.0);
+        game.piece().unwrap().rotation
+    }
+
+    #[test]
+    fn half_turns_only_happen_when_enabled() {
+        assert_eq!(rotation_after_half_turn(true), 2);
+        assert_eq!(rotation_after_half_turn(false), 0);
+    }
+
+    #[test]
+    fn clearing_every_segment_on_the_board_is_a_perfect_clear() {
+        let (_, events) = clear_two_lines_with_o_pieces();
+
+        assert!(events.contains(&GameEvent::LinesCleared(2)));
+        assert!(events.contains(&GameEvent::PerfectClear(2)));
+    }
+
+    #[test]
+    fn pieces_fall_to_the_floor_in_one_frame_at_20g() {
+        let mut game = Game::new(GameSettings { gravity: GravityCurve::Tgm, starting_level: 500, seed: Some(0), ..Default::default() });
+        game.step(&InputState::default(), 0.0);
+        game.step(&InputState::default(), 1.0 / 60.0);
+
+        let piece = game.piece().unwrap();
+        assert_eq!(piece.y, game.drop_position(piece));
+    }
+
+    #[test]
+    fn restarting_resets_the_gravity_to_the_starting_level() {
+        let mut game = Game::new(GameSettings { gravity: GravityCurve::Guideline, starting_level: 1, ..Default::default() });
+        game.level_up(&mut vec![]);
+        assert!(game.fall_timer.duration() < 1.0);
+
+        game.restart();
+        assert_eq!(game.fall_timer.duration(), GravityCurve::Guideline.fall_delay(1));
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use rand::prelude::*;
+use serde::Deserialize;
+
+use super::board::Board;
+use super::rotation::RotationSystem;
+
+/// A shape from the current piece set, identified by its index in the set
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shape(pub usize);
+impl Shape {
+    /// Gets the shape at the given index of the piece set, if the set has that many pieces
+    pub fn from_int(n: i32, pieces: &PieceSet) -> Option<Self> {
+        usize::try_from(n).ok().filter(|&index| index < pieces.len()).map(Shape)
+    }
+
+    pub fn get_random(rng: &mut (impl Rng + ?Sized), pieces: &PieceSet) -> Self {
+        Shape(rng.gen_range(0..pieces.len()))
+    }
+
+    pub fn get_color(&self, pieces: &PieceSet) -> [f32; 3] {
+        pieces.get(*self).color
+    }
+}
+
+/// The definition of one of the pieces in a piece set
+#[derive(Clone, Debug, Deserialize)]
+pub struct PieceDefinition {
+    /// The name of the piece (the standard tetrominoes are called L, J, S, Z, O, T and I)
+    pub name: String,
+    pub color: [f32; 3],
+    /// The offsets of the segments from the piece's position (Y pointing up) in each rotation state,
+    /// in the order spawn, clockwise, 180, anticlockwise. Pieces with fewer states repeat them
+    pub states: Vec<Vec<(i32, i32)>>,
+    /// The name of the kick table used when rotating the piece, if it can be kicked
+    pub kicks: Option<String>,
+    /// The name of the kick table used when rotating the piece by 180 degrees, if it can be kicked
+    #[serde(default)]
+    pub kicks_180: Option<String>
+}
+impl PieceDefinition {
+    /// Gets the offsets of the segments in one of the 4 rotation states
+    pub fn get_rotation_state(&self, rotation: usize) -> &[(i32, i32)] {
+        &self.states[rotation % self.states.len()]
+    }
+}
+
+/// The offsets that are tried in order when a rotation is blocked,
+/// for the rotations 0->1, 1->0, 1->2, 2->1, 2->3, 3->2, 3->0, 0->3 (or 0->2, 1->3, 2->0, 3->1 for 180 degree rotations)
+pub type KickTable = Vec<Vec<(i32, i32)>>;
+
+/// The set of pieces that a game is played with, usually loaded from a .pieces.ron file
+#[derive(Clone, Debug, Deserialize)]
+pub struct PieceSet {
+    pieces: Vec<PieceDefinition>,
+    #[serde(default)]
+    kick_tables: HashMap<String, KickTable>
+}
+impl Default for PieceSet {
+    fn default() -> Self {
+        PieceSet::standard()
+    }
+}
+
+impl PieceSet {
+    /// The 7 standard tetrominoes with SRS rotation, from assets/pieces/standard.pieces.ron
+    pub fn standard() -> Self {
+        PieceSet::from_ron(include_bytes!("../../assets/pieces/standard.pieces.ron")).expect("the standard piece set should be valid")
+    }
+
+    /// Reads a piece set from RON and checks that it can be played with
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, ron::Error> {
+        let pieces: PieceSet = ron::de::from_bytes(bytes)?;
+
+        let invalid = |message: String| -> Result<Self, ron::Error> { Err(serde::de::Error::custom(message)) };
+
+        if pieces.pieces.is_empty() {
+            return invalid("a piece set needs at least one piece".to_string());
+        }
+        for piece in pieces.pieces.iter() {
+            if piece.states.is_empty() || piece.states.iter().any(|state| state.is_empty()) {
+                return invalid(format!("piece {} needs at least one rotation state with segments in it", piece.name));
+            }
+            for (kicks, rotations) in [(&piece.kicks, 8), (&piece.kicks_180, 4)] {
+                if let Some(kicks) = kicks {
+                    match pieces.kick_tables.get(kicks) {
+                        Some(table) if table.len() == rotations => {},
+                        Some(_) => { return invalid(format!("kick table {} needs kicks for all {} rotations", kicks, rotations)); },
+                        None => { return invalid(format!("piece {} uses kick table {} which does not exist", piece.name, kicks)); }
+                    }
+                }
+            }
+        }
+
+        Ok(pieces)
+    }
+
+    /// The amount of pieces in the set
+    pub fn len(&self) -> usize {
+        self.pieces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    pub fn get(&self, shape: Shape) -> &PieceDefinition {
+        &self.pieces[shape.0]
+    }
+
+    /// Finds a shape by the name of its piece
+    pub fn find(&self, name: &str) -> Option<Shape> {
+        self.pieces.iter().position(|piece| piece.name == name).map(Shape)
+    }
+
+    /// The largest amount of segments that a piece in the set has
+    pub fn max_segments(&self) -> usize {
+        self.pieces.iter().flat_map(|piece| piece.states.iter()).map(|state| state.len()).max().unwrap_or(0)
+    }
+
+    /// Gets the kicks for rotating a shape from one rotation state to another, if the shape has a kick table
+    pub fn get_kicks(&self, shape: Shape, from_rotation: usize, to_rotation: usize) -> Option<&[(i32, i32)]> {
+        let piece = self.get(shape);
+
+        let (kicks, index) = match (from_rotation % 4, to_rotation % 4) {
+            (0, 1) => (&piece.kicks, 0),
+            (1, 0) => (&piece.kicks, 1),
+            (1, 2) => (&piece.kicks, 2),
+            (2, 1) => (&piece.kicks, 3),
+            (2, 3) => (&piece.kicks, 4),
+            (3, 2) => (&piece.kicks, 5),
+            (3, 0) => (&piece.kicks, 6),
+            (0, 3) => (&piece.kicks, 7),
+            (0, 2) => (&piece.kicks_180, 0),
+            (1, 3) => (&piece.kicks_180, 1),
+            (2, 0) => (&piece.kicks_180, 2),
+            (3, 1) => (&piece.kicks_180, 3),
+            _ => return None
+        };
+
+        Some(&self.kick_tables.get(kicks.as_ref()?)?[index])
+    }
+}
+
+/// The piece that is currently being controlled, positioned in board coordinates
+#[derive(Clone, Debug)]
+pub struct Piece {
+    pub shape: Shape,
+    pub x: i32,
+    pub y: i32,
+    pub rotation: usize,
+    /// How many rows the piece has been moved down by the player
+    pub dropped_pixels: usize,
+    /// The lowest row that the piece has been on
+    pub lowest_y: i32,
+    /// How many times moving or rotating the piece has reset the lock delay since it reached its lowest row
+    pub lock_resets: usize,
+    /// The kick that the last rotation used, if the last thing that moved the piece was a rotation
+    pub last_kick: Option<(i32, i32)>
+}
+impl Piece {
+    pub fn new(shape: Shape, x: i32, y: i32, rotation: usize) -> Self {
+        Piece { shape, x, y, rotation, dropped_pixels: 0, lowest_y: y, lock_resets: 0, last_kick: None }
+    }
+
+    /// Gets the board coordinates of every segment of the piece if it were at the given position and rotation
+    pub fn segment_positions_at(&self, rotation_system: &dyn RotationSystem, pieces: &PieceSet, x: i32, y: i32, rotation: usize) -> Vec<(i32, i32)> {
+        rotation_system.get_rotation_state(pieces, self.shape, rotation).iter().map(|(ox, oy)| (x + ox, y + oy)).collect()
+    }
+
+    /// Gets the board coordinates of every segment of the piece
+    pub fn segment_positions(&self, rotation_system: &dyn RotationSystem, pieces: &PieceSet) -> Vec<(i32, i32)> {
+        self.segment_positions_at(rotation_system, pieces, self.x, self.y, self.rotation)
+    }
+
+    /// Checks whether every segment of the piece would be on a free cell of the board at the given position and rotation
+    pub fn fits(&self, board: &Board, rotation_system: &dyn RotationSystem, pieces: &PieceSet, x: i32, y: i32, rotation: usize) -> bool {
+        rotation_system.get_rotation_state(pieces, self.shape, rotation).iter().all(|(ox, oy)| board.is_free(x + ox, y + oy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_kicks_uses_the_srs_tables() {
+        let pieces = PieceSet::standard();
+        let t = pieces.find("T").unwrap();
+        let i = pieces.find("I").unwrap();
+
+        assert_eq!(pieces.get_kicks(t, 0, 1), Some(&[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)][..]));
+        assert_eq!(pieces.get_kicks(t, 3, 0), Some(&[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)][..]));
+        assert_eq!(pieces.get_kicks(i, 0, 1), Some(&[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)][..]));
+        assert_eq!(pieces.get_kicks(i, 1, 0), Some(&[(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)][..]));
+    }
+
+    #[test]
+    fn get_kicks_without_a_kick_table() {
+        let pieces = PieceSet::standard();
+
+        assert_eq!(pieces.get_kicks(pieces.find("O").unwrap(), 0, 1), None);
+        assert_eq!(pieces.get_kicks(pieces.find("T").unwrap(), 0, 0), None);
+    }
+
+    #[test]
+    fn get_kicks_uses_the_180_table_for_half_turns() {
+        let pieces = PieceSet::standard();
+        let t = pieces.find("T").unwrap();
+
+        assert_eq!(pieces.get_kicks(t, 0, 2), Some(&[(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)][..]));
+        assert_eq!(pieces.get_kicks(t, 3, 1), Some(&[(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)][..]));
+        assert_eq!(pieces.get_kicks(pieces.find("O").unwrap(), 0, 2), None);
+    }
+}
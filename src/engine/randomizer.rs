@@ -0,0 +1,225 @@
+//! Randomizers decide the order in which pieces are spawned
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+use rand::prelude::*;
+
+use super::piece::{PieceSet, Shape};
+
+pub trait Randomizer: Debug + Send + Sync {
+    /// Chooses the shape of the next piece
+    fn next_shape(&mut self, rng: &mut dyn RngCore, pieces: &PieceSet) -> Shape;
+
+    /// Forgets about the shapes that have been chosen so far, for example because the piece set has changed
+    fn reset(&mut self);
+
+    /// Copies the randomizer into a new box, so that settings containing one can be cloned
+    fn clone_box(&self) -> Box<dyn Randomizer>;
+}
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Every shape is chosen completely randomly, which can lead to long droughts and floods
+#[derive(Clone, Debug, Default)]
+pub struct Uniform;
+impl Randomizer for Uniform {
+    fn next_shape(&mut self, rng: &mut dyn RngCore, pieces: &PieceSet) -> Shape {
+        Shape::get_random(rng, pieces)
+    }
+
+    fn reset(&mut self) {}
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Shapes are drawn from a shuffled bag containing every shape a certain amount of times, the bag is refilled once it is empty
+#[derive(Clone, Debug)]
+pub struct Bag {
+    /// How many times each shape is in a full bag
+    copies: usize,
+    /// The shapes that are left in the current bag, in the order they will be drawn
+    bag: Vec<Shape>
+}
+impl Bag {
+    pub fn new(copies: usize) -> Self {
+        Bag { copies: copies.max(1), bag: vec![] }
+    }
+
+    /// Every shape once per bag, the modern standard (7 pieces with the standard piece set)
+    pub fn seven() -> Self {
+        Bag::new(1)
+    }
+
+    /// Every shape twice per bag (14 pieces with the standard piece set)
+    pub fn fourteen() -> Self {
+        Bag::new(2)
+    }
+}
+impl Randomizer for Bag {
+    fn next_shape(&mut self, rng: &mut dyn RngCore, pieces: &PieceSet) -> Shape {
+        if self.bag.is_empty() {
+            self.bag = (0..pieces.len()).flat_map(|index| std::iter::repeat_n(Shape(index), self.copies)).collect();
+            self.bag.shuffle(rng);
+        }
+
+        self.bag.pop().unwrap()
+    }
+
+    fn reset(&mut self) {
+        self.bag.clear();
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// NES tetris: a shape is rolled with one extra "reroll" option, if that or the previous shape is rolled,
+/// the shape is rolled once more without any restrictions
+#[derive(Clone, Debug, Default)]
+pub struct NesReroll {
+    last: Option<Shape>
+}
+impl Randomizer for NesReroll {
+    fn next_shape(&mut self, rng: &mut dyn RngCore, pieces: &PieceSet) -> Shape {
+        let roll = rng.gen_range(0..=pieces.len());
+
+        let shape = if roll == pieces.len() || Some(Shape(roll)) == self.last {
+            Shape::get_random(rng, pieces)
+        } else {
+            Shape(roll)
+        };
+
+        self.last = Some(shape);
+        shape
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// TGM: the last 4 shapes are remembered and a shape is rolled up to a certain amount of times until it is not one of them,
+/// the last roll is used either way. The first piece is never an S, Z or O
+#[derive(Clone, Debug)]
+pub struct TgmHistory {
+    /// How many times a shape is rolled at most
+    rolls: usize,
+    /// The names of the shapes the history starts with
+    initial_history: [&'static str; 4],
+    /// The last 4 shapes, oldest first (None until the first piece is chosen)
+    history: Option<VecDeque<Shape>>
+}
+impl TgmHistory {
+    pub fn new(rolls: usize, initial_history: [&'static str; 4]) -> Self {
+        TgmHistory { rolls: rolls.max(1), initial_history, history: None }
+    }
+
+    /// The first TGM: 4 rolls, the history starts as Z Z Z Z
+    pub fn tgm1() -> Self {
+        TgmHistory::new(4, ["Z", "Z", "Z", "Z"])
+    }
+
+    /// TGM2: 6 rolls, the history starts as Z S S Z
+    pub fn tgm2() -> Self {
+        TgmHistory::new(6, ["Z", "S", "S", "Z"])
+    }
+}
+impl Randomizer for TgmHistory {
+    fn next_shape(&mut self, rng: &mut dyn RngCore, pieces: &PieceSet) -> Shape {
+        let shape = match &mut self.history {
+            Some(history) => {
+                let mut shape = Shape::get_random(rng, pieces);
+                for _ in 1..self.rolls {
+                    if !history.contains(&shape) {
+                        break;
+                    }
+                    shape = Shape::get_random(rng, pieces);
+                }
+                shape
+            },
+            None => {
+                let initial_history = self.initial_history.iter().filter_map(|name| pieces.find(name)).collect();
+                self.history = Some(initial_history);
+
+                // the first piece is never one that could create an overhang right away (if there are any other pieces)
+                let first_shapes: Vec<Shape> = (0..pieces.len()).map(Shape)
+                    .filter(|&shape| !["S", "Z", "O"].contains(&pieces.get(shape).name.as_str()))
+                    .collect();
+                match first_shapes.choose(rng) {
+                    Some(&shape) => shape,
+                    None => Shape::get_random(rng, pieces)
+                }
+            }
+        };
+
+        if let Some(history) = &mut self.history {
+            history.push_back(shape);
+            while history.len() > 4 {
+                history.pop_front();
+            }
+        }
+
+        shape
+    }
+
+    fn reset(&mut self) {
+        self.history = None;
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn bag_hands_out_every_shape_once_per_bag() {
+        let pieces = PieceSet::standard();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut bag = Bag::seven();
+
+        for _ in 0..10 {
+            let mut shapes: Vec<usize> = (0..7).map(|_| bag.next_shape(&mut rng, &pieces).0).collect();
+            shapes.sort_unstable();
+            assert_eq!(shapes, (0..7).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn fourteen_bag_hands_out_every_shape_twice_per_bag() {
+        let pieces = PieceSet::standard();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut bag = Bag::fourteen();
+
+        let mut shapes: Vec<usize> = (0..14).map(|_| bag.next_shape(&mut rng, &pieces).0).collect();
+        shapes.sort_unstable();
+        assert_eq!(shapes, (0..7).flat_map(|shape| [shape, shape]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn tgm_never_starts_with_an_overhang_piece() {
+        let pieces = PieceSet::standard();
+        let overhang_pieces = ["S", "Z", "O"].map(|name| pieces.find(name).unwrap());
+
+        for seed in 0..50 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut randomizer = TgmHistory::tgm2();
+            assert!(!overhang_pieces.contains(&randomizer.next_shape(&mut rng, &pieces)));
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Rotation systems decide how pieces look in each rotation state and where they may be moved ("kicked") when a rotation is blocked
+
+use std::fmt::Debug;
+
+use super::board::Board;
+use super::piece::{Piece, PieceSet, Shape};
+
+/// The rotation states of one of the standard tetrominoes, each containing the offsets of the 4 segments
+/// from the piece's position (Y pointing up), in the order spawn, clockwise, 180, anticlockwise
+type RotationStates = [[(i32, i32); 4]; 4];
+
+pub trait RotationSystem: Debug + Send + Sync {
+    /// Gets the offsets of all segments of a shape in one of the 4 rotation states (0 = spawn, 1 = clockwise)
+    fn get_rotation_state<'a>(&'a self, pieces: &'a PieceSet, shape: Shape, rotation: usize) -> &'a [(i32, i32)];
+
+    /// Gets the offsets that should be tried in order when rotating the piece into another rotation state,
+    /// the first one where the rotated piece fits gets used
+    fn get_kicks(&self, board: &Board, pieces: &PieceSet, piece: &Piece, to_rotation: usize) -> Vec<(i32, i32)>;
+}
+
+/// Rotates the piece with the given rotation system if it fits on the board in the new rotation state after any of the kicks,
+/// returns the kick that was used
+pub fn rotate(rotation_system: &dyn RotationSystem, board: &Board, pieces: &PieceSet, piece: &mut Piece, to_rotation: usize) -> Option<(i32, i32)> {
+    let kick = rotation_system.get_kicks(board, pieces, piece, to_rotation).into_iter()
+        .find(|&(kx, ky)| piece.fits(board, rotation_system, pieces, piece.x + kx, piece.y + ky, to_rotation))?;
+
+    piece.x += kick.0;
+    piece.y += kick.1;
+    piece.rotation = to_rotation % 4;
+
+    Some(kick)
+}
+
+/// Gets the rotation states from a table of the standard tetrominoes, if the shape is one of them
+fn get_standard_states(pieces: &PieceSet, shape: Shape, tables: [&'static RotationStates; 7]) -> Option<&'static RotationStates> {
+    let index = ["L", "J", "S", "Z", "O", "T", "I"].iter().position(|name| *name == pieces.get(shape).name)?;
+
+    Some(tables[index])
+}
+
+/// The Super Rotation System used by modern tetris games, which uses the rotation states and wall kicks of the piece set
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Srs;
+impl RotationSystem for Srs {
+    fn get_rotation_state<'a>(&'a self, pieces: &'a PieceSet, shape: Shape, rotation: usize) -> &'a [(i32, i32)] {
+        pieces.get(shape).get_rotation_state(rotation)
+    }
+
+    fn get_kicks(&self, _board: &Board, pieces: &PieceSet, piece: &Piece, to_rotation: usize) -> Vec<(i32, i32)> {
+        match pieces.get_kicks(piece.shape, piece.rotation, to_rotation) {
+            Some(kicks) => kicks.to_vec(),
+            None => vec![(0, 0)]
+        }
+    }
+}
+
+/// The Nintendo Rotation System from NES tetris, right-handed and without any kicks.
+/// Pieces that are not standard tetrominoes use the piece set's rotation states
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Nrs;
+impl RotationSystem for Nrs {
+    fn get_rotation_state<'a>(&'a self, pieces: &'a PieceSet, shape: Shape, rotation: usize) -> &'a [(i32, i32)] {
+        match get_standard_states(pieces, shape, [&NRS_L_STATES, &NRS_J_STATES, &NRS_S_STATES, &NRS_Z_STATES, &NRS_O_STATES, &NRS_T_STATES, &NRS_I_STATES]) {
+            Some(states) => &states[rotation % 4],
+            None => pieces.get(shape).get_rotation_state(rotation)
+        }
+    }
+
+    fn get_kicks(&self, _board: &Board, _pieces: &PieceSet, _piece: &Piece, _to_rotation: usize) -> Vec<(i32, i32)> {
+        vec![(0, 0)]
+    }
+}
+
+/// The Arika Rotation System from the TGM games, pieces are kicked one segment right or left if the rotation is blocked.
+/// J, L and T pieces do not get kicked if the first blocked segment (reading from the top left) is in their center column.
+/// Pieces that are not standard tetrominoes use the piece set's rotation states
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ars;
+impl RotationSystem for Ars {
+    fn get_rotation_state<'a>(&'a self, pieces: &'a PieceSet, shape: Shape, rotation: usize) -> &'a [(i32, i32)] {
+        match get_standard_states(pieces, shape, [&ARS_L_STATES, &ARS_J_STATES, &ARS_S_STATES, &ARS_Z_STATES, &ARS_O_STATES, &ARS_T_STATES, &ARS_I_STATES]) {
+            Some(states) => &states[rotation % 4],
+            None => pieces.get(shape).get_rotation_state(rotation)
+        }
+    }
+
+    fn get_kicks(&self, board: &Board, pieces: &PieceSet, piece: &Piece, to_rotation: usize) -> Vec<(i32, i32)> {
+        match pieces.get(piece.shape).name.as_str() {
+            "I" | "O" => vec![(0, 0)],
+            "J" | "L" | "T" => {
+                let mut rotated_offsets = self.get_rotation_state(pieces, piece.shape, to_rotation).to_vec();
+                // read from the top left, row by row
+                rotated_offsets.sort_by_key(|&(ox, oy)| (-oy, ox));
+
+                let first_blocked = rotated_offsets.iter().find(|&&(ox, oy)| !board.is_free(piece.x + ox, piece.y + oy));
+
+                match first_blocked {
+                    Some((0, _)) => vec![(0, 0)],
+                    _ => vec![(0, 0), (1, 0), (-1, 0)]
+                }
+            },
+            _ => vec![(0, 0), (1, 0), (-1, 0)]
+        }
+    }
+}
+
+/// The rotation states this game originally had, without any kicks.
+/// Pieces that are not standard tetrominoes use the piece set's rotation states
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Legacy;
+impl RotationSystem for Legacy {
+    fn get_rotation_state<'a>(&'a self, pieces: &'a PieceSet, shape: Shape, rotation: usize) -> &'a [(i32, i32)] {
+        match get_standard_states(pieces, shape, [&LEGACY_L_STATES, &LEGACY_J_STATES, &LEGACY_S_STATES, &LEGACY_Z_STATES, &LEGACY_O_STATES, &LEGACY_T_STATES, &LEGACY_I_STATES]) {
+            Some(states) => &states[rotation % 4],
+            None => pieces.get(shape).get_rotation_state(rotation)
+        }
+    }
+
+    fn get_kicks(&self, _board: &Board, _pieces: &PieceSet, _piece: &Piece, _to_rotation: usize) -> Vec<(i32, i32)> {
+        vec![(0, 0)]
+    }
+}
+
+// NRS ROTATION STATES
+// S, Z and I only have two rotation states, their vertical states are on the right side of the center
+const NRS_L_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (1, 0), (-1, -1)],
+    [(0, 1), (0, 0), (0, -1), (-1, 1)],
+    [(1, 0), (0, 0), (-1, 0), (1, 1)],
+    [(0, -1), (0, 0), (0, 1), (1, -1)]
+];
+const NRS_J_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (1, 0), (1, -1)],
+    [(0, 1), (0, 0), (0, -1), (-1, -1)],
+    [(1, 0), (0, 0), (-1, 0), (-1, 1)],
+    [(0, -1), (0, 0), (0, 1), (1, 1)]
+];
+const NRS_S_STATES: RotationStates = [
+    [(0, 0), (1, 0), (-1, -1), (0, -1)],
+    [(0, 1), (0, 0), (1, 0), (1, -1)],
+    [(0, 0), (1, 0), (-1, -1), (0, -1)],
+    [(0, 1), (0, 0), (1, 0), (1, -1)]
+];
+const NRS_Z_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (0, -1), (1, -1)],
+    [(1, 1), (1, 0), (0, 0), (0, -1)],
+    [(-1, 0), (0, 0), (0, -1), (1, -1)],
+    [(1, 1), (1, 0), (0, 0), (0, -1)]
+];
+const NRS_O_STATES: RotationStates = [[(-1, 0), (0, 0), (-1, -1), (0, -1)]; 4];
+const NRS_T_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (1, 0), (0, -1)],
+    [(0, 1), (0, 0), (0, -1), (-1, 0)],
+    [(1, 0), (0, 0), (-1, 0), (0, 1)],
+    [(0, -1), (0, 0), (0, 1), (1, 0)]
+];
+const NRS_I_STATES: RotationStates = [
+    [(-2, 0), (-1, 0), (0, 0), (1, 0)],
+    [(0, 2), (0, 1), (0, 0), (0, -1)],
+    [(-2, 0), (-1, 0), (0, 0), (1, 0)],
+    [(0, 2), (0, 1), (0, 0), (0, -1)]
+];
+
+// ARS ROTATION STATES
+// every state is aligned to the bottom of the 3x3 box around the center segment, instead of rotating around it
+const ARS_L_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (1, 0), (-1, -1)],
+    [(-1, 1), (0, 1), (0, 0), (0, -1)],
+    [(1, 0), (-1, -1), (0, -1), (1, -1)],
+    [(0, 1), (0, 0), (0, -1), (1, -1)]
+];
+const ARS_J_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (1, 0), (1, -1)],
+    [(0, 1), (0, 0), (-1, -1), (0, -1)],
+    [(-1, 0), (-1, -1), (0, -1), (1, -1)],
+    [(0, 1), (1, 1), (0, 0), (0, -1)]
+];
+const ARS_S_STATES: RotationStates = [
+    [(0, 0), (1, 0), (-1, -1), (0, -1)],
+    [(-1, 1), (-1, 0), (0, 0), (0, -1)],
+    [(0, 0), (1, 0), (-1, -1), (0, -1)],
+    [(-1, 1), (-1, 0), (0, 0), (0, -1)]
+];
+const ARS_Z_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (0, -1), (1, -1)],
+    [(1, 1), (0, 0), (1, 0), (0, -1)],
+    [(-1, 0), (0, 0), (0, -1), (1, -1)],
+    [(1, 1), (0, 0), (1, 0), (0, -1)]
+];
+const ARS_O_STATES: RotationStates = [[(0, 0), (1, 0), (0, -1), (1, -1)]; 4];
+const ARS_T_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (1, 0), (0, -1)],
+    [(0, 1), (-1, 0), (0, 0), (0, -1)],
+    [(0, 0), (-1, -1), (0, -1), (1, -1)],
+    [(0, 1), (0, 0), (1, 0), (0, -1)]
+];
+const ARS_I_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (1, 0), (2, 0)],
+    [(1, 1), (1, 0), (1, -1), (1, -2)],
+    [(-1, 0), (0, 0), (1, 0), (2, 0)],
+    [(1, 1), (1, 0), (1, -1), (1, -2)]
+];
+
+// LEGACY ROTATION STATES
+const LEGACY_L_STATES: RotationStates = [
+    [(0, 1), (0, 0), (0, -1), (1, -1)],
+    [(1, 0), (0, 0), (-1, 0), (-1, -1)],
+    [(0, -1), (0, 0), (0, 1), (-1, 1)],
+    [(-1, 0), (0, 0), (1, 0), (1, 1)]
+];
+const LEGACY_J_STATES: RotationStates = [
+    [(0, 1), (0, 0), (0, -1), (-1, -1)],
+    [(1, 0), (0, 0), (-1, 0), (-1, 1)],
+    [(0, -1), (0, 0), (0, 1), (1, 1)],
+    [(-1, 0), (0, 0), (1, 0), (1, -1)]
+];
+const LEGACY_S_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (0, 1), (1, 1)],
+    [(-1, 2), (-1, 1), (0, 1), (0, 0)],
+    [(-1, 0), (0, 0), (0, 1), (1, 1)],
+    [(-1, 2), (-1, 1), (0, 1), (0, 0)]
+];
+const LEGACY_Z_STATES: RotationStates = [
+    [(-1, 1), (0, 1), (0, 0), (1, 0)],
+    [(0, 2), (0, 1), (-1, 1), (-1, 0)],
+    [(-1, 1), (0, 1), (0, 0), (1, 0)],
+    [(0, 2), (0, 1), (-1, 1), (-1, 0)]
+];
+const LEGACY_O_STATES: RotationStates = [[(0, 0), (0, 1), (1, 0), (1, 1)]; 4];
+const LEGACY_T_STATES: RotationStates = [
+    [(-1, 0), (0, 0), (1, 0), (0, -1)],
+    [(0, 1), (0, 0), (0, -1), (-1, 0)],
+    [(1, 0), (0, 0), (-1, 0), (0, 1)],
+    [(0, -1), (0, 0), (0, 1), (1, 0)]
+];
+const LEGACY_I_STATES: RotationStates = [
+    [(0, 0), (0, 1), (0, 2), (0, 3)],
+    [(-2, 0), (-1, 0), (0, 0), (1, 0)],
+    [(0, 0), (0, 1), (0, 2), (0, 3)],
+    [(-2, 0), (-1, 0), (0, 0), (1, 0)]
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srs_kicks_pieces_off_the_left_wall() {
+        let pieces = PieceSet::standard();
+        let board = Board::new(10, 20, 2);
+
+        // a vertical I against the wall sticks out on the left when it turns flat
+        let mut piece = Piece::new(pieces.find("I").unwrap(), 0, 5, 3);
+        assert_eq!(rotate(&Srs, &board, &pieces, &mut piece, 0), Some((1, 0)));
+        assert_eq!((piece.x, piece.y, piece.rotation), (1, 5, 0));
+
+        let mut piece = Piece::new(pieces.find("T").unwrap(), 0, 5, 1);
+        assert_eq!(rotate(&Srs, &board, &pieces, &mut piece, 2), Some((1, 0)));
+        assert_eq!((piece.x, piece.y, piece.rotation), (1, 5, 2));
+    }
+
+    #[test]
+    fn srs_kicks_pieces_off_the_right_wall() {
+        let pieces = PieceSet::standard();
+        let board = Board::new(10, 20, 2);
+
+        let mut piece = Piece::new(pieces.find("T").unwrap(), 9, 5, 3);
+        assert_eq!(rotate(&Srs, &board, &pieces, &mut piece, 0), Some((-1, 0)));
+        assert_eq!((piece.x, piece.y, piece.rotation), (8, 5, 0));
+    }
+
+    #[test]
+    fn srs_rotates_in_place_when_there_is_room() {
+        let pieces = PieceSet::standard();
+        let board = Board::new(10, 20, 2);
+
+        let mut piece = Piece::new(pieces.find("T").unwrap(), 4, 5, 0);
+        assert_eq!(rotate(&Srs, &board, &pieces, &mut piece, 1), Some((0, 0)));
+        assert_eq!((piece.x, piece.y, piece.rotation), (4, 5, 1));
+    }
+
+    #[test]
+    fn nrs_does_not_kick() {
+        let pieces = PieceSet::standard();
+        let board = Board::new(10, 20, 2);
+
+        let mut piece = Piece::new(pieces.find("T").unwrap(), 9, 5, 3);
+        assert_eq!(rotate(&Nrs, &board, &pieces, &mut piece, 0), None);
+        assert_eq!((piece.x, piece.rotation), (9, 3));
+    }
+
+    #[test]
+    fn rotations_cannot_move_segments_above_the_vanish_zone() {
+        let pieces = PieceSet::standard();
+        let board = Board::new(10, 20, 2);
+
+        // a flat I on the top row would stick out above the board if it turned upright in place
+        let mut piece = Piece::new(pieces.find("I").unwrap(), 4, 21, 0);
+        rotate(&Srs, &board, &pieces, &mut piece, 1);
+        assert!(Srs.get_rotation_state(&pieces, piece.shape, piece.rotation).iter().all(|&(_, oy)| piece.y + oy < board.total_height() as i32));
+    }
+}
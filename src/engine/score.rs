@@ -0,0 +1,125 @@
+use super::scoring::{Combo, Placement, ScoringRules};
+
+#[derive(Clone, Debug, Default)]
+pub struct ScoreResource {
+    score: usize,
+    lines: usize,
+    /// How many pieces in a row after the first one have cleared lines, None if the last piece didn't clear any
+    combo: Option<usize>,
+    /// How many lines the pieces of the current combo have cleared
+    combo_lines: usize,
+    /// How many difficult clears (tetrises and T-spins) in a row after the first one there have been without an easy clear in between
+    back_to_back: Option<usize>
+}
+impl ScoreResource {
+    /// Keeps count of lines, combos and back-to-backs after a piece was placed and increases score by what the rules say it is worth.
+    /// Returns the score rewarded
+    pub fn reward_placement(&mut self, rules: &dyn ScoringRules, placement: &Placement) -> usize {
+        // the combo that this piece continues, pieces that don't clear lines end it instead
+        let combo = match placement.lines {
+            0 => Combo::default(),
+            _ => Combo { pieces: self.combo.map_or(0, |combo| combo + 1), lines: self.combo_lines }
+        };
+
+        let back_to_back = if placement.lines == 0 {
+            // T-spins without lines don't break back-to-backs either
+            self.combo = None;
+            self.combo_lines = 0;
+            false
+        }
+        else {
+            self.combo = Some(self.combo.map_or(0, |combo| combo + 1));
+            self.combo_lines += placement.lines;
+
+            if placement.lines >= 4 || placement.t_spin.is_some() {
+                self.back_to_back = Some(self.back_to_back.map_or(0, |back_to_back| back_to_back + 1));
+                self.back_to_back() > 0
+            }
+            else {
+                self.back_to_back = None;
+                false
+            }
+        };
+
+        let score = rules.placement_score(placement, combo, back_to_back);
+        self.score += score;
+        self.lines += placement.lines;
+        score
+    }
+
+    /// Reset score to 0
+    pub fn reset(&mut self) {
+        self.score = 0;
+        self.lines = 0;
+        self.combo = None;
+        self.combo_lines = 0;
+        self.back_to_back = None;
+    }
+
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// How many pieces in a row after the first one have cleared lines
+    pub fn combo(&self) -> usize {
+        self.combo.unwrap_or(0)
+    }
+
+    /// How many difficult clears in a row after the first one there have been
+    pub fn back_to_back(&self) -> usize {
+        self.back_to_back.unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::scoring::{Guideline, Tgm};
+    use crate::engine::t_spin::TSpin;
+
+    #[test]
+    fn combos_and_back_to_backs_are_counted_and_rewarded() {
+        let mut score = ScoreResource::default();
+        let mut place = |lines, t_spin| {
+            let reward = score.reward_placement(&Guideline, &Placement { lines, t_spin, level: 1, ..Default::default() });
+            (reward, score.combo(), score.back_to_back())
+        };
+
+        assert_eq!(place(4, None), (800, 0, 0));
+        assert_eq!(place(4, None), (1250, 1, 1));
+        // a T-spin without lines ends the combo but keeps the back-to-back going
+        assert_eq!(place(0, Some(TSpin::Full)), (400, 0, 1));
+        assert_eq!(place(2, Some(TSpin::Full)), (1800, 0, 2));
+        // an easy clear ends the back-to-back but not the combo
+        assert_eq!(place(1, None), (150, 1, 0));
+        assert_eq!(place(4, None), (900, 2, 0));
+        assert_eq!(place(0, None), (0, 0, 0));
+    }
+
+    #[test]
+    fn reset_forgets_everything() {
+        let mut score = ScoreResource::default();
+        for _ in 0..3 {
+            score.reward_placement(&Guideline, &Placement { lines: 4, level: 1, ..Default::default() });
+        }
+        score.reset();
+
+        assert_eq!((score.score(), score.lines(), score.combo(), score.back_to_back()), (0, 0, 0, 0));
+        assert_eq!(score.reward_placement(&Guideline, &Placement { lines: 4, level: 1, ..Default::default() }), 800);
+    }
+
+    #[test]
+    fn combos_remember_how_many_lines_they_cleared() {
+        let mut score = ScoreResource::default();
+        let mut place = |lines| score.reward_placement(&Tgm, &Placement { lines, ..Default::default() });
+
+        assert_eq!(place(4), 4);
+        assert_eq!(place(2), 14);
+        assert_eq!(place(0), 0);
+        assert_eq!(place(2), 2);
+    }
+}
@@ -0,0 +1,189 @@
+//! Scoring rules decide how much score placing pieces and clearing lines is worth
+
+use std::fmt::Debug;
+
+use super::t_spin::TSpin;
+
+/// Everything about a placed piece that can be worth score
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Placement {
+    /// How many lines the piece cleared (can be 0)
+    pub lines: usize,
+    pub t_spin: Option<TSpin>,
+    /// Did the cleared lines leave the board completely empty?
+    pub perfect_clear: bool,
+    /// The level that the piece was placed on
+    pub level: usize,
+    /// How many rows the piece was moved down by the player before being placed (soft drop and sonic drop)
+    pub soft_dropped_rows: usize,
+    /// How many rows the piece was hard dropped
+    pub hard_dropped_rows: usize
+}
+
+/// The pieces in a row right before a placed one that have cleared lines
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Combo {
+    pub pieces: usize,
+    /// How many lines those pieces cleared together
+    pub lines: usize
+}
+
+pub trait ScoringRules: Debug + Send + Sync {
+    /// How much score a placed piece is worth. `combo` is made up of the pieces before this one that have cleared lines,
+    /// `back_to_back` is whether this is a difficult clear (a tetris or a T-spin) right after another one
+    fn placement_score(&self, placement: &Placement, combo: Combo, back_to_back: bool) -> usize;
+
+    /// The grade that the given score has earned, for rules that have grades
+    fn grade(&self, _score: usize) -> Option<&'static str> {
+        None
+    }
+}
+
+/// The original Nintendo scoring: only lines and soft dropping are worth score
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Nes;
+impl ScoringRules for Nes {
+    fn placement_score(&self, placement: &Placement, _combo: Combo, _back_to_back: bool) -> usize {
+        let base = match placement.lines {
+            0 => 0,
+            1 => 40,
+            2 => 100,
+            3 => 300,
+            _ => 1200
+        };
+        base * (placement.level + 1) + placement.soft_dropped_rows
+    }
+}
+
+/// Modern scoring, where T-spins, back-to-backs, combos and perfect clears are worth extra
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Guideline;
+impl ScoringRules for Guideline {
+    fn placement_score(&self, placement: &Placement, combo: Combo, back_to_back: bool) -> usize {
+        let level = placement.level.max(1);
+
+        let clear = match (placement.t_spin, placement.lines) {
+            (None, 0) => 0,
+            (None, 1) => 100,
+            (None, 2) => 300,
+            (None, 3) => 500,
+            (None, _) => 800,
+            (Some(TSpin::Mini), 0) => 100,
+            (Some(TSpin::Mini), 1) => 200,
+            (Some(TSpin::Mini), _) => 400,
+            (Some(TSpin::Full), 0) => 400,
+            (Some(TSpin::Full), 1) => 800,
+            (Some(TSpin::Full), 2) => 1200,
+            (Some(TSpin::Full), _) => 1600
+        };
+        // back-to-back clears are worth half as much again
+        let clear = if back_to_back { clear * 3 / 2 } else { clear };
+
+        let perfect_clear = match placement.lines {
+            _ if !placement.perfect_clear => 0,
+            1 => 800,
+            2 => 1200,
+            3 => 1800,
+            _ if back_to_back => 3200,
+            _ => 2000
+        };
+
+        (clear + 50 * combo.pieces + perfect_clear) * level + placement.soft_dropped_rows + 2 * placement.hard_dropped_rows
+    }
+}
+
+/// Arcade scoring, where lines are worth more the higher the level is and clearing the board quadruples the score.
+/// The score earns grades from 9 up to S9
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tgm;
+impl Tgm {
+    /// The score needed for each grade, from the lowest
+    const GRADES: [(usize, &'static str); 18] = [
+        (0, "9"), (400, "8"), (800, "7"), (1400, "6"), (2000, "5"), (3500, "4"), (5500, "3"), (8000, "2"), (12000, "1"),
+        (16000, "S1"), (22000, "S2"), (30000, "S3"), (40000, "S4"), (52000, "S5"), (66000, "S6"), (82000, "S7"), (100000, "S8"), (120000, "S9")
+    ];
+}
+impl ScoringRules for Tgm {
+    fn placement_score(&self, placement: &Placement, combo: Combo, _back_to_back: bool) -> usize {
+        if placement.lines == 0 {
+            return 0;
+        }
+
+        // the combo multiplier starts at 1 and every piece of the combo adds 2 for each line it cleared after its first one,
+        // so a combo of singles is worth nothing extra
+        let combo = 1 + 2 * (combo.lines - combo.pieces);
+        let bravo = if placement.perfect_clear { 4 } else { 1 };
+        ((placement.level + placement.lines).div_ceil(4) + placement.soft_dropped_rows + 2 * placement.hard_dropped_rows) * placement.lines * combo * bravo
+    }
+
+    fn grade(&self, score: usize) -> Option<&'static str> {
+        Tgm::GRADES.iter().rev().find(|(required, _)| score >= *required).map(|(_, grade)| *grade)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(lines: usize, level: usize) -> Placement {
+        Placement { lines, level, ..Default::default() }
+    }
+
+    /// A combo of the given amount of pieces that each cleared a single line
+    fn singles(pieces: usize) -> Combo {
+        Combo { pieces, lines: pieces }
+    }
+
+    #[test]
+    fn nes_line_scores() {
+        assert_eq!(Nes.placement_score(&clear(1, 0), Combo::default(), false), 40);
+        assert_eq!(Nes.placement_score(&clear(2, 0), Combo::default(), false), 100);
+        assert_eq!(Nes.placement_score(&clear(3, 0), Combo::default(), false), 300);
+        assert_eq!(Nes.placement_score(&clear(4, 0), Combo::default(), false), 1200);
+        assert_eq!(Nes.placement_score(&clear(4, 9), Combo::default(), false), 12000);
+        // soft drops are worth a point a row, combos and back-to-backs are worth nothing
+        assert_eq!(Nes.placement_score(&Placement { soft_dropped_rows: 5, ..clear(0, 3) }, Combo::default(), false), 5);
+        assert_eq!(Nes.placement_score(&clear(4, 0), singles(3), true), 1200);
+    }
+
+    #[test]
+    fn guideline_line_and_drop_scores() {
+        assert_eq!(Guideline.placement_score(&clear(1, 1), Combo::default(), false), 100);
+        assert_eq!(Guideline.placement_score(&clear(4, 1), Combo::default(), false), 800);
+        assert_eq!(Guideline.placement_score(&clear(3, 2), Combo::default(), false), 1000);
+        assert_eq!(Guideline.placement_score(&Placement { soft_dropped_rows: 3, hard_dropped_rows: 10, ..clear(0, 5) }, Combo::default(), false), 23);
+    }
+
+    #[test]
+    fn guideline_special_clear_scores() {
+        let t_spin_double = Placement { t_spin: Some(TSpin::Full), ..clear(2, 1) };
+        assert_eq!(Guideline.placement_score(&t_spin_double, Combo::default(), false), 1200);
+        assert_eq!(Guideline.placement_score(&Placement { t_spin: Some(TSpin::Mini), ..clear(0, 1) }, Combo::default(), false), 100);
+        // back-to-back clears are worth 1.5 times as much, combos add 50 a piece
+        assert_eq!(Guideline.placement_score(&clear(4, 1), Combo::default(), true), 1200);
+        assert_eq!(Guideline.placement_score(&clear(1, 2), singles(3), false), 500);
+        assert_eq!(Guideline.placement_score(&Placement { perfect_clear: true, ..clear(1, 1) }, Combo::default(), false), 900);
+        assert_eq!(Guideline.placement_score(&Placement { perfect_clear: true, ..clear(4, 1) }, Combo::default(), true), 4400);
+    }
+
+    #[test]
+    fn tgm_scores_grow_with_the_level_combo_and_bravo() {
+        assert_eq!(Tgm.placement_score(&clear(0, 5), Combo::default(), false), 0);
+        assert_eq!(Tgm.placement_score(&clear(1, 0), Combo::default(), false), 1);
+        assert_eq!(Tgm.placement_score(&clear(4, 10), Combo::default(), false), 16);
+        assert_eq!(Tgm.placement_score(&Placement { perfect_clear: true, ..clear(4, 10) }, Combo::default(), false), 64);
+        assert_eq!(Tgm.placement_score(&Placement { soft_dropped_rows: 3, hard_dropped_rows: 2, ..clear(1, 0) }, Combo::default(), false), 8);
+        // a double after a tetris is multiplied by 1 + 2 * 3, singles don't raise the multiplier
+        assert_eq!(Tgm.placement_score(&clear(2, 0), Combo { pieces: 1, lines: 4 }, false), 14);
+        assert_eq!(Tgm.placement_score(&clear(2, 0), singles(3), false), 2);
+    }
+
+    #[test]
+    fn tgm_grades() {
+        assert_eq!(Nes.grade(100000), None);
+        assert_eq!(Tgm.grade(0), Some("9"));
+        assert_eq!(Tgm.grade(399), Some("9"));
+        assert_eq!(Tgm.grade(400), Some("8"));
+        assert_eq!(Tgm.grade(1000000), Some("S9"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Detecting T-spins, where a T piece gets rotated into a spot that it could not have been moved into
+
+use super::board::Board;
+use super::piece::{Piece, PieceSet};
+use super::rotation::RotationSystem;
+
+/// The kinds of T-spins, which are rewarded differently
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TSpin {
+    /// Only one of the corners in front of the T is blocked
+    Mini,
+    /// Both of the corners in front of the T are blocked
+    Full
+}
+
+/// Checks whether placing the piece where it is would be a T-spin, using the 3-corner rule:
+/// the piece has to be a T that was rotated into place, and at least 3 of the corners around its center segment have to be blocked.
+/// A mini T-spin becomes a full one if the piece got there with one of the long kicks (1 column and 2 rows)
+pub fn detect_t_spin(board: &Board, rotation_system: &dyn RotationSystem, pieces: &PieceSet, piece: &Piece) -> Option<TSpin> {
+    if pieces.get(piece.shape).name != "T" {
+        return None;
+    }
+    let (kick_x, kick_y) = piece.last_kick?;
+
+    // the center of the T is the segment with the other three next to it (not always at (0, 0), depending on the rotation system),
+    // the segment that sticks out is the only one without a segment opposite of it around the center
+    let offsets = rotation_system.get_rotation_state(pieces, piece.shape, piece.rotation);
+    let is_segment = |x: i32, y: i32| offsets.contains(&(x, y));
+    let &(center_x, center_y) = offsets.iter().find(|&&(x, y)| [(1, 0), (-1, 0), (0, 1), (0, -1)].iter().filter(|&&(dx, dy)| is_segment(x + dx, y + dy)).count() == 3)?;
+    let (nub_x, nub_y) = offsets.iter()
+        .map(|&(x, y)| (x - center_x, y - center_y))
+        .find(|&(dx, dy)| (dx, dy) != (0, 0) && !is_segment(center_x - dx, center_y - dy))?;
+
+    let is_blocked = |(cx, cy): (i32, i32)| !board.is_free(piece.x + center_x + cx, piece.y + center_y + cy);
+
+    let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+    if corners.iter().filter(|&&corner| is_blocked(corner)).count() < 3 {
+        return None;
+    }
+
+    let front_corners = [(nub_x - nub_y, nub_y + nub_x), (nub_x + nub_y, nub_y - nub_x)];
+    if front_corners.iter().all(|&corner| is_blocked(corner)) || (kick_x.abs() == 1 && kick_y.abs() == 2) {
+        Some(TSpin::Full)
+    }
+    else {
+        Some(TSpin::Mini)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::piece::Shape;
+    use crate::engine::rotation::{Ars, Srs};
+
+    /// A board with the given cells filled
+    fn board_with(cells: &[(i32, i32)]) -> Board {
+        let mut board = Board::new(10, 20, 2);
+        for &(x, y) in cells {
+            board.set(x, y, Some(Shape(0)));
+        }
+        board
+    }
+
+    /// A T pointing up at (4, 1) that was just rotated into place with the given kick
+    fn rotated_t(pieces: &PieceSet, kick: Option<(i32, i32)>) -> Piece {
+        let mut piece = Piece::new(pieces.find("T").unwrap(), 4, 1, 0);
+        piece.last_kick = kick;
+        piece
+    }
+
+    #[test]
+    fn both_front_corners_blocked_is_a_full_t_spin() {
+        let pieces = PieceSet::standard();
+        let board = board_with(&[(3, 0), (3, 2), (5, 2)]);
+
+        assert_eq!(detect_t_spin(&board, &Srs, &pieces, &rotated_t(&pieces, Some((0, 0)))), Some(TSpin::Full));
+    }
+
+    #[test]
+    fn one_front_corner_blocked_is_a_mini_t_spin() {
+        let pieces = PieceSet::standard();
+        let board = board_with(&[(3, 0), (5, 0), (3, 2)]);
+
+        assert_eq!(detect_t_spin(&board, &Srs, &pieces, &rotated_t(&pieces, Some((0, 0)))), Some(TSpin::Mini));
+        // unless the T got there with a long kick
+        assert_eq!(detect_t_spin(&board, &Srs, &pieces, &rotated_t(&pieces, Some((-1, -2)))), Some(TSpin::Full));
+    }
+
+    #[test]
+    fn no_t_spin_without_a_rotation_or_three_corners() {
+        let pieces = PieceSet::standard();
+
+        assert_eq!(detect_t_spin(&board_with(&[(3, 0), (3, 2), (5, 2)]), &Srs, &pieces, &rotated_t(&pieces, None)), None);
+        assert_eq!(detect_t_spin(&board_with(&[(3, 2), (5, 2)]), &Srs, &pieces, &rotated_t(&pieces, Some((0, 0)))), None);
+
+        let mut piece = rotated_t(&pieces, Some((0, 0)));
+        piece.shape = pieces.find("J").unwrap();
+        assert_eq!(detect_t_spin(&board_with(&[(3, 0), (5, 0), (3, 2), (5, 2)]), &Srs, &pieces, &piece), None);
+    }
+
+    #[test]
+    fn corners_are_found_around_the_center_segment_of_any_rotation_system() {
+        let pieces = PieceSet::standard();
+        // the ARS T points up in rotation state 2, with its center segment below the piece's position
+        let mut piece = Piece::new(pieces.find("T").unwrap(), 4, 1, 2);
+        piece.last_kick = Some((0, 0));
+
+        assert_eq!(detect_t_spin(&board_with(&[(3, 1)]), &Ars, &pieces, &piece), Some(TSpin::Mini));
+        assert_eq!(detect_t_spin(&board_with(&[(3, 1), (5, 1)]), &Ars, &pieces, &piece), Some(TSpin::Full));
+    }
+}
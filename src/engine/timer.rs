@@ -0,0 +1,52 @@
+/// A repeating timer that is advanced manually by the game's step function
+#[derive(Clone, Debug)]
+pub struct Timer {
+    duration: f32,
+    elapsed: f32,
+    times_finished: usize
+}
+impl Timer {
+    /// Creates a new timer that finishes every `duration` seconds
+    pub fn from_seconds(duration: f32) -> Self {
+        Timer { duration, elapsed: 0.0, times_finished: 0 }
+    }
+
+    /// Advances the timer by `delta` seconds
+    pub fn tick(&mut self, delta: f32) {
+        self.elapsed += delta;
+        self.times_finished = if self.duration > 0.0 { (self.elapsed / self.duration) as usize } else { 1 };
+
+        if self.times_finished > 0 {
+            self.elapsed = if self.duration > 0.0 { self.elapsed % self.duration } else { 0.0 };
+        }
+    }
+
+    /// Did the timer finish during the last tick?
+    pub fn just_finished(&self) -> bool {
+        self.times_finished > 0
+    }
+
+    /// How many times the timer finished during the last tick, which can be more than once if the tick was longer than the duration
+    pub fn times_finished(&self) -> usize {
+        self.times_finished
+    }
+
+    /// How far the timer is into its duration, from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        if self.duration > 0.0 { self.elapsed / self.duration } else { 1.0 }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: f32) {
+        self.duration = duration;
+    }
+
+    /// Starts counting from 0 again
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+        self.times_finished = 0;
+    }
+}
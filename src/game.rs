@@ -1,14 +1,10 @@
-use std::time::Duration;
-
-use crate::audio::PlaySoundEvent;
-use crate::consts::*;
-use crate::board::*;
+use bevy_tetris::engine::*;
 use crate::helper::*;
 use crate::audio::*;
-use crate::score::*;
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
 use bevy::ecs::event::Events;
 use bevy::prelude::*;
-use rand::prelude::*;
+use bevy::reflect::TypeUuid;
 
 // RESOURCES
 struct SegmentMaterialResource {
@@ -26,493 +22,314 @@ impl FromWorld for SegmentMaterialResource {
     }
 }
 
-pub struct GameState {
-    pub started: bool,
-    pub starting_level: usize,
-    pub level: usize, // TODO: would be cool if i added negative levels lmao
-    pub has_piece: bool
+/// The keys that control the game, these can be changed by inserting this resource before adding the GamePlugin
+pub struct KeyBindings {
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub down: KeyCode,
+    pub up: KeyCode,
+    pub rotate_clockwise: KeyCode,
+    pub rotate_anticlockwise: KeyCode,
+    pub rotate_180: KeyCode,
+    pub hold: KeyCode,
+    pub hard_drop: KeyCode
 }
-
-/// Contains the next piece to spawn and the current 7-bag state, if enabled
-pub struct PieceSelectionResource {
-    pub next_piece: Option<Piece>
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+            down: KeyCode::Down,
+            up: KeyCode::Up,
+            rotate_clockwise: KeyCode::X,
+            rotate_anticlockwise: KeyCode::Z,
+            rotate_180: KeyCode::A,
+            hold: KeyCode::C,
+            hard_drop: KeyCode::Space
+        }
+    }
 }
 
+/// Changes whenever the game has changed in a way that should be shown. Stepping the game needs mutable access to it,
+/// which marks the game itself as changed every frame, so the display systems check this instead
 #[derive(Default)]
-pub struct InputState {
-    /// Is the up key being held down?
-    pub up: bool,
-    /// Is the down key being held down?
-    pub down: bool,
-    /// Is the left key being held down?
-    pub left: bool,
-    /// Is the right key being held down?
-    pub right: bool,
-    /// Was the up key just pressed?
-    pub up_jp: bool,
-    /// Was the down key just pressed?
-    pub down_jp: bool,
-    /// Was the left key just pressed?
-    pub left_jp: bool,
-    /// Was the right key just pressed?
-    pub right_jp: bool,
-    pub rotate_clockwise_jp: bool,
-    pub rotate_anticlockwise_jp: bool
+pub struct GameChanged;
+
+/// The piece set that the game is played with
+struct PieceSetResource {
+    handle: Handle<PieceSetAsset>
 }
+impl FromWorld for PieceSetResource {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
 
-pub struct FallTimer(Timer);
-pub struct InputTimer(Timer);
-pub struct DropInputTimer(Timer);
+        PieceSetResource { handle: asset_server.load("pieces/standard.pieces.ron") }
+    }
+}
 
-// COMPONENTS
+// ASSETS
+/// A piece set loaded from a .pieces.ron file
+#[derive(TypeUuid)]
+#[uuid = "6f0c3a51-2b8e-4d47-9a0e-1c5f7d9e2b34"]
+pub struct PieceSetAsset(pub PieceSet);
 
-// EVENTS
-pub struct PlacePieceEvent;
-pub struct CheckLinesEvent;
-pub struct LevelUpEvent;
-pub struct LossEvent;
+#[derive(Default)]
+pub struct PieceSetLoader;
+impl AssetLoader for PieceSetLoader {
+    fn load<'a>(&'a self, bytes: &'a [u8], load_context: &'a mut LoadContext) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let pieces = PieceSet::from_ron(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(PieceSetAsset(pieces)));
+            Ok(())
+        })
+    }
 
-fn spawn_pieces(mut commands: Commands, mut game_state: ResMut<GameState>, asset_server: Res<AssetServer>, mut piece_selection: ResMut<PieceSelectionResource>) {
-    let mut rng = rand::thread_rng();
+    fn extensions(&self) -> &[&str] {
+        &["pieces.ron"]
+    }
+}
 
-    if game_state.has_piece == false {
-        let mut transform = Transform::from_scale(Vec3::new(SEGMENT_SIZE, SEGMENT_SIZE, 1.0));
+// COMPONENTS
+/// A sprite displaying one of the segments of the current piece
+#[derive(Copy, Clone, Component)]
+pub struct Segment {
+    pub segment_index: usize
+}
 
-        let (pxw, pyw) = coords_to_world(PIECE_SPAWN_XP as f32, PIECE_SPAWN_YP as f32);
-        let piece_pos = Vec3::new(pxw, pyw, 0.0);
+/// A translucent sprite displaying one of the segments of the ghost piece, where the current piece would land
+#[derive(Copy, Clone, Component)]
+pub struct GhostSegment {
+    pub segment_index: usize
+}
 
-        let shape: Shape;
-        let rotation: usize;
+/// A sprite displaying a single cell of the board
+#[derive(Component)]
+pub struct TowerSegment {
+    pub x: usize,
+    pub y: usize
+}
 
-        // spawn the piece that was selected to be next
-        match &piece_selection.next_piece {
-            // there already is a next piece selected, use it.
-            Some(next_piece) => {
-                shape = next_piece.shape;
-                rotation = next_piece.rotation;
-            },
-            // choose a new piece to spawn next
-            None => {
-                shape = Shape::get_random();
-                rotation = rng.gen_range(0..=3);
-            }
-        }
+/// Creates a hidden segment sprite at the given board position
+fn segment_bundle(segment_material: &SegmentMaterialResource, config: &BoardConfig, layout: &BoardLayout, x: f32, y: f32) -> SpriteBundle {
+    let (xw, yw) = coords_to_world(config, layout, x, y);
+
+    SpriteBundle {
+        texture: segment_material.piece_texture.clone(),
+        sprite: Sprite {
+            custom_size: Some(Vec2::new(1.0, 1.0)),
+            ..Default::default()
+        },
+        transform: Transform {
+            translation: Vec3::new(xw, yw, 0.0),
+            scale: Vec3::new(layout.segment_size, layout.segment_size, 1.0),
+            ..Default::default()
+        },
+        visibility: Visibility { is_visible: false },
+        ..Default::default()
+    }
+}
 
-        let mut segments: Vec<Entity> = vec![];
-
-        let mut index = 0;
-
-        // spawn a sprite for each segment in the shape
-        for _ in 1..=4 {
-            //(transform.translation.x, transform.translation.y) = coords_to_world(BOARD_WIDTH as f32 / 2.0 + pos.x, BOARD_HEIGHT as f32 - 2.0 + pos.y);
-            let pos = shape.get_rotation_offset(index, rotation);
-            transform.translation = piece_pos + pos.extend(0.0) * SEGMENT_SIZE;
-
-            let segment = commands
-                .spawn_bundle(SpriteBundle {
-                    texture: asset_server.load("textures/segment.png"),
-                    sprite: Sprite {
-                        color: shape.get_color(),
-                        custom_size: Some(Vec2::new(1.0, 1.0)),
-                        ..Default::default()
-                    },
-                    transform,
-                    ..Default::default()
-                })
-                .insert(Segment { segment_index: index })
-                .id();
-
-            segments.push(segment);
-            index += 1;
-        }
+/// Spawns a hidden sprite for every segment of the piece and every cell of the board, the sync systems show them when needed
+fn spawn_segments(mut commands: Commands, game: Res<Game>, layout: Res<BoardLayout>, segment_material: Res<SegmentMaterialResource>) {
+    let segment_bundle = |x: f32, y: f32| segment_bundle(&segment_material, game.board_config(), &layout, x, y);
 
-        // TEMP
-        let next_piece_shape = Shape::get_random();
-        let next_piece_rot = rng.gen_range(0..=3);
-        // let next_piece_shape = Shape::get_random();
-        // let next_piece_rot = rng.gen_range(0..=3);
-
-        // select the next piece to spawn
-        piece_selection.next_piece = Some(Piece { 
-            shape: next_piece_shape,
-            position: Vec3::new(pxw, pyw, 0.0),
-            rotation: next_piece_rot,
-            segment_entities: vec![],
-            dropped_pixels: 0
-        });
-        
-        // create the piece entity containing the shape, a random rotation and all the segment entities
+    for segment_index in 0..game.pieces().max_segments() {
         commands
-            .spawn()
-            .insert(Piece { shape, position: piece_pos, rotation, segment_entities: segments, dropped_pixels: 0 });
-
-        // TEMP
-        game_state.has_piece = true;
+            .spawn_bundle(segment_bundle(0.0, 0.0))
+            .insert(Segment { segment_index });
+        commands
+            .spawn_bundle(segment_bundle(0.0, 0.0))
+            .insert(GhostSegment { segment_index });
     }
-}
 
-pub fn piece_movement_input(input: Res<Input<KeyCode>>, mut input_state: ResMut<InputState>, mut input_timer: ResMut<InputTimer>, mut drop_input_timer: ResMut<DropInputTimer>) {
-    input_state.up = input.pressed(KeyCode::Up);
-    input_state.down = input.pressed(KeyCode::Down);
-    input_state.left = input.pressed(KeyCode::Left);
-    input_state.right = input.pressed(KeyCode::Right);
-    input_state.up_jp = input.just_pressed(KeyCode::Up);
-    input_state.down_jp = input.just_pressed(KeyCode::Down);
-    input_state.left_jp = input.just_pressed(KeyCode::Left);
-    input_state.right_jp = input.just_pressed(KeyCode::Right);
-    input_state.rotate_clockwise_jp = input.just_pressed(KeyCode::X);
-    input_state.rotate_anticlockwise_jp = input.just_pressed(KeyCode::Z);
-
-    // reset the side movement input timer so the player can hold the button to move
-    if input_state.left_jp || input_state.right_jp {
-        input_timer.0.reset();
-    }
-    // reset the drop input timer (faster than side to side movement)
-    if input_state.down_jp {
-        drop_input_timer.0.reset();
+    for y in 0..game.board().height() {
+        for x in 0..game.board().width() {
+            commands
+                .spawn_bundle(segment_bundle(x as f32, y as f32))
+                .insert(TowerSegment { x, y });
+        }
     }
 }
 
-/// Handles pieces being moved by the player and gravity, collision checking
-pub fn move_pieces(mut pieces: Query<&mut Piece>, mut segments: Query<(&mut Transform, &mut Segment)>, time: Res<Time>, tower_segments: Query<(&Transform, &TowerSegment), Without<Segment>>, mut timer: ResMut<FallTimer>, input_state: Res<InputState>, mut input_timer: ResMut<InputTimer>, mut drop_input_timer: ResMut<DropInputTimer>, mut place_events: ResMut<Events<PlacePieceEvent>>) {
-    timer.0.tick(time.delta());
-    input_timer.0.tick(time.delta());
-    drop_input_timer.0.tick(time.delta());
-
-    for mut piece in pieces.iter_mut() {
-        // whether or not the piece is being dropped manually this frame
-        let mut dropping = false;
-
-        // convert the movement input into movement
-        let mut movement = Vec3::new(
-            if input_state.left_jp || (input_state.left && input_timer.0.just_finished()) { -1.0 } else if input_state.right_jp || (input_state.right && input_timer.0.just_finished()) { 1.0 } else { 0.0 },
-            if input_state.down_jp || (input_state.down && drop_input_timer.0.just_finished()) { dropping = true; -1.0 } else { 0.0 },
-            0.0
-        );
-
-        // // TEMP: in the future, perhaps just make the newest move override the older one
-        // if (movement.x > 0.0 || movement.x < 0.0) && movement.y < 0.0 {
-        //     movement.x = 0.0;
-        //     movement.y = 0.0;
-        // }
-
-        // apply "gravity"
-        if timer.0.just_finished() {
-            movement.y = -1.0;
-        }
-
-        // rotation and rotation collision / edge checking
-        if input_state.rotate_clockwise_jp || input_state.rotate_anticlockwise_jp {
-            let mut can_rotate = true;
-            let mut desired_rotation = piece.rotation;
-            // change rotation
-            desired_rotation = if input_state.rotate_clockwise_jp { piece.rotation + 1 } else { if piece.rotation > 0 { piece.rotation - 1 } else { 3 } };
-            // wrap around
-            if desired_rotation > 3 { desired_rotation = 0; }
-
-            // loop through all the segments of the piece to check if their desired rotation pos is valid
-            for segment_entity in piece.segment_entities.iter().cloned() {
-                // get the transform and segment of the current segment
-                let (_, segment) = segments.get_mut(segment_entity).unwrap();
-                // get the target position after rotation in normal and pixel form
-                let rotated_pos = piece.position + (movement * SEGMENT_SIZE) + Vec2::extend(piece.shape.get_rotation_offset(segment.segment_index, desired_rotation) * SEGMENT_SIZE, 0.0);
-                let (rxp, ryp) = coords_to_pixel(rotated_pos.x, rotated_pos.y);
-
-                // is the rotated pos even on the board?
-                if Board::is_on_board(rxp, ryp, true) == false {
-                    can_rotate = false;
-                    break;
-                }
-
-                // check if the rotated position overlaps any tower segments
-                for (tower_transform, _) in tower_segments.iter() {
-                    let (txp, typ) = coords_to_pixel(tower_transform.translation.x, tower_transform.translation.y);
-    
-                    // same X as the tower segment and about to hit it - place the piece
-                    if rxp + movement.x as i32 == txp && ryp + movement.y as i32 == typ {
-                        can_rotate = false;
-                        break;
-                    }
-                }
-
-                // if we have already discovered that the piece can't be rotated, quit the loop
-                if can_rotate == false { break };
-            }
-
-            // the piece can be rotated without issue, do it.
-            if can_rotate == true {
-                piece.rotation = desired_rotation;
+pub fn piece_movement_input(input: Res<Input<KeyCode>>, key_bindings: Res<KeyBindings>, mut input_state: ResMut<InputState>) {
+    input_state.up = input.pressed(key_bindings.up);
+    input_state.down = input.pressed(key_bindings.down);
+    input_state.left = input.pressed(key_bindings.left);
+    input_state.right = input.pressed(key_bindings.right);
+    input_state.up_jp = input.just_pressed(key_bindings.up);
+    input_state.down_jp = input.just_pressed(key_bindings.down);
+    input_state.left_jp = input.just_pressed(key_bindings.left);
+    input_state.right_jp = input.just_pressed(key_bindings.right);
+    input_state.rotate_clockwise = input.pressed(key_bindings.rotate_clockwise);
+    input_state.rotate_anticlockwise = input.pressed(key_bindings.rotate_anticlockwise);
+    input_state.rotate_180 = input.pressed(key_bindings.rotate_180);
+    input_state.hold = input.pressed(key_bindings.hold);
+    input_state.rotate_clockwise_jp = input.just_pressed(key_bindings.rotate_clockwise);
+    input_state.rotate_anticlockwise_jp = input.just_pressed(key_bindings.rotate_anticlockwise);
+    input_state.rotate_180_jp = input.just_pressed(key_bindings.rotate_180);
+    input_state.hold_jp = input.just_pressed(key_bindings.hold);
+    input_state.hard_drop_jp = input.just_pressed(key_bindings.hard_drop);
+}
 
-                for segment_entity in piece.segment_entities.iter().cloned() {
-                    let (mut transform, segment) = segments.get_mut(segment_entity).unwrap();
-                    transform.translation = piece.position + Vec2::extend(piece.shape.get_rotation_offset(segment.segment_index, piece.rotation) * SEGMENT_SIZE, 0.0);
-                }
-            }
-        }
+/// Shows or hides the ghost piece
+fn toggle_ghost_piece(input: Res<Input<KeyCode>>, mut game: ResMut<Game>, mut game_changed: ResMut<GameChanged>) {
+    if input.just_pressed(KeyCode::G) {
+        let enabled = !game.ghost_piece_enabled();
+        game.set_ghost_piece_enabled(enabled);
+        game_changed.set_changed();
+    }
+}
 
-        let mut place_piece = false;
-        let mut place_diagonal = false;
-        let mut was_blocked = false;
-
-        // check for collisions with edges and tower segments
-        for segment_entity in piece.segment_entities.iter().cloned() {
-            let (transform, _) = segments.get(segment_entity).unwrap();
-
-            // check if hitting the side edge
-            let (xp, yp) = coords_to_pixel(transform.translation.x, transform.translation.y);
-
-            // piece is trying to move out of the board to the left or right, set horizontal movement to 0
-            if (xp + movement.x as i32) < 0 {
-                movement.x = 0.0;
-            }
-            else if (xp + movement.x as i32) > BOARD_WIDTH as i32 - 1 {
-                movement.x = 0.0;
-            }
-
-            // piece is at the bottom of the board and trying to fall through it, place it
-            if (yp + movement.y as i32) < 0 {
-                place_piece = true;
-                movement.y = 0.0;
-            }
-
-            // OPTIMIZABLE: i can break the entire loop if one of these fires, no need to check other segments
-            // iterate through all the current tower segments to check if side movement is going to collide with any
-            for (tower_transform, _) in tower_segments.iter() {
-                let (txp, typ) = coords_to_pixel(tower_transform.translation.x, tower_transform.translation.y);
-
-                // check if dropping a piece directly on top of a tower segment - always placement
-                if movement.x == 0.0 {
-                    if xp == txp && yp + movement.y as i32 == typ {
-                        movement.y = 0.0;
-                        place_piece = true;
+/// Starts playing with the piece set once it has been loaded (or changed)
+fn apply_piece_set(mut commands: Commands, mut game: ResMut<Game>, mut game_changed: ResMut<GameChanged>, piece_set: Res<PieceSetResource>, piece_set_assets: Res<Assets<PieceSetAsset>>, mut asset_events: EventReader<AssetEvent<PieceSetAsset>>, segment_material: Res<SegmentMaterialResource>, layout: Res<BoardLayout>, segments: Query<&Segment>, ghost_segments: Query<&GhostSegment>) {
+    for event in asset_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } if *handle == piece_set.handle => {
+                if let Some(asset) = piece_set_assets.get(handle) {
+                    game.set_piece_set(asset.0.clone());
+                    game_changed.set_changed();
+
+                    // make sure there are enough sprites for the largest piece
+                    for segment_index in segments.iter().count()..game.pieces().max_segments() {
+                        commands
+                            .spawn_bundle(segment_bundle(&segment_material, game.board_config(), &layout, 0.0, 0.0))
+                            .insert(Segment { segment_index });
                     }
-                }
-
-                // check if only trying to move a piece into the side of a tower segment - always blocked
-                if movement.y == 0.0 {
-                    if yp == typ && xp + movement.x as i32 == txp {
-                        movement.x = 0.0;
-                        was_blocked = true;
+                    for segment_index in ghost_segments.iter().count()..game.pieces().max_segments() {
+                        commands
+                            .spawn_bundle(segment_bundle(&segment_material, game.board_config(), &layout, 0.0, 0.0))
+                            .insert(GhostSegment { segment_index });
                     }
                 }
+            },
+            _ => {}
+        }
+    }
+}
 
-                // check if trying to move a piece diagonally into a tower segment - i am going to force these to also be placements, because the tile would end up on top of it and going down, if X movement was applied first
-                if (movement.y > 0.0 || movement.y < 0.0) && (movement.x > 0.0 || movement.x < 0.0) {
-                    if xp + movement.x as i32 == txp && yp + movement.y as i32 == typ {
-                        movement.y = 0.0;
-                        place_diagonal = true;
-                    }
-                }
-            }
+/// Advances the game by the frame's time and passes on what happened in it
+pub fn step_game(mut game: ResMut<Game>, mut game_changed: ResMut<GameChanged>, time: Res<Time>, input_state: Res<InputState>, mut game_events: ResMut<Events<GameEvent>>, mut play_sound_events: ResMut<Events<PlaySoundEvent>>) {
+    let piece_before = game.piece().map(|piece| (piece.shape, piece.x, piece.y, piece.rotation));
+    let events = game.step(&input_state, time.delta_seconds());
 
-            if was_blocked == true { place_diagonal = false; }
-        }
+    // only redraw when something happened, the piece moved or cleared lines are fading out
+    let piece_after = game.piece().map(|piece| (piece.shape, piece.x, piece.y, piece.rotation));
+    if !events.is_empty() || piece_before != piece_after || game.phase() == Phase::LineClear {
+        game_changed.set_changed();
+    }
 
-        // move the piece's position along
-        piece.position += movement * SEGMENT_SIZE;
-
-        let mut piece_has_been_placed = false;
-
-        // move segments contained in this piece
-        for segment_entity in piece.segment_entities.iter().cloned() {
-            let (mut transform, _) = segments.get_mut(segment_entity).unwrap();
-
-            transform.translation += movement * SEGMENT_SIZE;
-            Board::clamp_to_board(&mut transform.translation);
-            
-            if (place_piece || place_diagonal) && piece_has_been_placed == false {
-                place_events.send(PlacePieceEvent {});
-                timer.0.reset();
-                input_timer.0.reset();
-                drop_input_timer.0.reset();
-                // we still want to move the other pieces as well, but not spawn a new piece for every one of them
-                piece_has_been_placed = true;
-            }
+    // a hard dropped piece has its own sound instead of the normal placing sound
+    let hard_dropped = events.iter().any(|event| matches!(event, GameEvent::HardDropped(_)));
+
+    for event in events {
+        match event {
+            GameEvent::HardDropped(_) => { play_sound_events.send(PlaySoundEvent { sound: Sound::HardDrop }); },
+            GameEvent::PiecePlaced if !hard_dropped => { play_sound_events.send(PlaySoundEvent { sound: Sound::PiecePlace }); },
+            GameEvent::TSpin(..) => { play_sound_events.send(PlaySoundEvent { sound: Sound::TSpin }); },
+            GameEvent::LinesCleared(4) => { play_sound_events.send(PlaySoundEvent { sound: Sound::TetrisClear }); },
+            GameEvent::LinesCleared(_) => { play_sound_events.send(PlaySoundEvent { sound: Sound::LineClear }); },
+            GameEvent::PerfectClear(_) => { play_sound_events.send(PlaySoundEvent { sound: Sound::PerfectClear }); },
+            GameEvent::LevelUp(level) => { println!("Leveled up to level {}", level); },
+            GameEvent::Loss { .. } => { play_sound_events.send(PlaySoundEvent { sound: Sound::GameOver }); },
+            _ => {}
         }
 
-        if dropping == true && place_piece == false && place_diagonal == false && piece_has_been_placed == false {
-            piece.dropped_pixels += 1;
-        }
+        game_events.send(event);
     }
 }
 
-pub fn place_piece(mut commands: Commands, mut pieces: Query<(Entity, &mut Piece)>, mut game_state: ResMut<GameState>, mut place_event_reader: EventReader<PlacePieceEvent>, mut loss_events: ResMut<Events<LossEvent>>, mut check_lines_events: ResMut<Events<CheckLinesEvent>>, mut play_sound_events: ResMut<Events<PlaySoundEvent>>, mut score_resource: ResMut<ScoreResource>) {
-    let mut loss_event_sent = false;
-    
-    for _ in place_event_reader.iter() {
-        for (piece_entity, mut piece) in pieces.iter_mut() {
-            for segment_entity in piece.segment_entities.iter() {
-                commands
-                    .entity(*segment_entity)
-                    .remove::<Segment>()
-                    .insert(TowerSegment { });
-            }
-
-            // award score for dropped pixels
-            score_resource.reward_drop_score(piece.dropped_pixels, 1);
-            
-            // TEMP: this would not work well for all pieces
-            let (_, pyp) = coords_to_pixel(0.0, piece.position.y);
-            if pyp >= PIECE_SPAWN_YP - 2 && loss_event_sent == false {
-                loss_events.send(LossEvent {});
-                loss_event_sent = true;
-            }
-
-            piece.segment_entities.clear();
-            // despawn the piece (because we'll be spawning a new one)
-            commands
-                .entity(piece_entity)
-                .remove::<Piece>()
-                .despawn();
-
-            play_sound_events.send(PlaySoundEvent { sound: Sound::PiecePlace });
-            check_lines_events.send(CheckLinesEvent {});
-        }
-        if loss_event_sent == false {
-            game_state.has_piece = false;
-        }
+/// Positions the sprites of the current piece's segments according to its board position
+pub fn sync_piece_segments(game: Res<Game>, game_changed: Res<GameChanged>, layout: Res<BoardLayout>, mut segments: Query<(&mut Transform, &mut Sprite, &mut Visibility, &Segment)>) {
+    if !game_changed.is_changed() {
+        return;
     }
-}
 
-pub fn check_lines(mut commands: Commands, mut tower_segments: Query<(&mut Transform, &mut Sprite, Entity), (Without<Segment>, With<TowerSegment>)>, mut check_lines_reader: EventReader<CheckLinesEvent>, game_state: Res<GameState>, mut play_sound_events: ResMut<Events<PlaySoundEvent>>, mut score_resource: ResMut<ScoreResource>, mut level_up_events: ResMut<Events<LevelUpEvent>>) {
-    for _ in check_lines_reader.iter() {
-        let mut line_segment_counts = [0; BOARD_HEIGHT];
+    for (mut transform, mut sprite, mut visibility, segment) in segments.iter_mut() {
+        let segment_position = game.piece().and_then(|piece| Some((piece, *game.segment_positions(piece).get(segment.segment_index)?)));
 
-        // get the count of tower segments in each layer of the tower
-        for (tower_transform,_, _) in tower_segments.iter() {
-            let (_, layer) = coords_to_pixel(0.0, tower_transform.translation.y);
+        match segment_position {
+            Some((piece, (x, y))) => {
+                let (xw, yw) = coords_to_world(game.board_config(), &layout, x as f32, y as f32);
 
-            line_segment_counts[layer as usize] += 1; 
+                transform.translation = Vec3::new(xw, yw, 0.0);
+                sprite.color = Color::from(piece.shape.get_color(game.pieces()));
+                // segments in the vanish zone above the board are hidden
+                visibility.is_visible = y < game.board().height() as i32;
+            },
+            None => { visibility.is_visible = false; }
         }
+    }
+}
 
-        let mut full_layers: Vec<i32> = vec![];
-        // check if any lines are full (also keep count of that, for singles, doubles, triples, tetrises)
-        for i in 0..line_segment_counts.len() {
-            if line_segment_counts[i] == 10 {
-                full_layers.push(i as i32);
-            }
-            else if line_segment_counts[i] > 10 {
-                println!("WARNING: A line was cleared with MORE than 10 segments in it");
-                full_layers.push(i as i32);
-            }
-        }
+/// Positions the translucent sprites of the ghost piece where the current piece would land
+pub fn sync_ghost_segments(game: Res<Game>, game_changed: Res<GameChanged>, layout: Res<BoardLayout>, mut ghost_segments: Query<(&mut Transform, &mut Sprite, &mut Visibility, &GhostSegment)>) {
+    if !game_changed.is_changed() {
+        return;
+    }
 
-        // clear the lines and make lines above them fall
-        for (mut tower_transform, mut sprite, tower_segment_entity) in tower_segments.iter_mut() {
-            let (_, typ) = coords_to_pixel(0.0, tower_transform.translation.y);
-
-            // the tower segment is within one of complete lines
-            if full_layers.contains(&typ) {
-                commands
-                    .entity(tower_segment_entity)
-                    .remove::<TowerSegment>()
-                    .despawn();
-            } else {
-                for i in 0..full_layers.len() {
-                    if typ > full_layers[i] {
-                        //sprite.color = Color::CRIMSON;
-                        tower_transform.translation.y -= SEGMENT_SIZE;
-                    }
-                }
-            }
-        }
+    let ghost_piece = game.ghost_piece();
 
-        // reward score for lines (also increases the line counter on the score)
-        score_resource.reward_line_score(full_layers.len(), game_state.level);
-        
-        // check if enough lines have been cleared to progress to the next level (depending on whether or not this is the starting level)
-        // on the starting level, where more lines need to be cleared
-        if game_state.level == game_state.starting_level {
-            if score_resource.lines() >= (game_state.starting_level + 1) * LINES_PER_LEVEL {
-                level_up_events.send(LevelUpEvent {});
-            }
-        }
-        // on levels past the starting level
-        else if game_state.level > game_state.starting_level {
-            if score_resource.lines() >= (game_state.starting_level + 1) * LINES_PER_LEVEL + (game_state.level - game_state.starting_level) * LINES_PER_LEVEL {
-                level_up_events.send(LevelUpEvent {});
-            }
-        }
+    for (mut transform, mut sprite, mut visibility, ghost_segment) in ghost_segments.iter_mut() {
+        let segment_position = ghost_piece.as_ref().and_then(|piece| Some((piece, *game.segment_positions(piece).get(ghost_segment.segment_index)?)));
 
-        // play line clear sounds
-        if full_layers.len() == 4 {
-            play_sound_events.send(PlaySoundEvent { sound: Sound::TetrisClear });
-        }
-        else if full_layers.len() > 0 {
-            play_sound_events.send(PlaySoundEvent { sound: Sound::LineClear });
+        match segment_position {
+            Some((piece, (x, y))) => {
+                let (xw, yw) = coords_to_world(game.board_config(), &layout, x as f32, y as f32);
+                let [r, g, b] = piece.shape.get_color(game.pieces());
+
+                // behind the current piece, which can overlap it
+                transform.translation = Vec3::new(xw, yw, -0.1);
+                sprite.color = Color::rgba(r, g, b, GHOST_PIECE_ALPHA);
+                visibility.is_visible = y < game.board().height() as i32;
+            },
+            None => { visibility.is_visible = false; }
         }
     }
 }
 
-pub fn level_up(mut level_up_events: EventReader<LevelUpEvent>, mut game_state: ResMut<GameState>, mut fall_timer: ResMut<FallTimer>) {
-    for _ in level_up_events.iter() {
-        game_state.level += 1;
-        println!("Leveled up to level {}", game_state.level);
-
-        // make pieces fall faster now
-        let new_duration = Duration::from_millis((fall_timer.0.duration().as_millis() as f32 / LEVEL_SPEED_INCREASE_PERCENTAGE) as u64);
-        //fall_timer.0.reset();
-        fall_timer.0.set_duration(new_duration);
+/// Shows the tower segments of filled board cells in the color of the shape that filled them
+pub fn sync_tower_segments(game: Res<Game>, game_changed: Res<GameChanged>, mut tower_segments: Query<(&mut Sprite, &mut Visibility, &TowerSegment)>) {
+    if !game_changed.is_changed() {
+        return;
     }
-}
 
-pub fn restart(mut commands: Commands, mut pieces: Query<(&mut Piece, Entity)>, tower_segments: Query<Entity, (Without<Segment>, With<TowerSegment>)>, mut loss_reader: EventReader<LossEvent>, mut game_state: ResMut<GameState>, mut score_resource: ResMut<ScoreResource>, mut play_sound_events: ResMut<Events<PlaySoundEvent>>) {
-    for _ in loss_reader.iter() {
-        // erase all tower segments
-        for tower_entity in tower_segments.iter() {
-            commands
-                .entity(tower_entity)
-                .remove::<TowerSegment>()
-                .despawn();
-        }
-
-        // erase the current piece
-        for (piece, piece_entity) in pieces.iter_mut() {
-            for segment_entity in piece.segment_entities.iter() {
-                commands
-                    .entity(*segment_entity)
-                    .remove::<Segment>()
-                    .despawn();
-            }
-            commands
-                .entity(piece_entity)
-                .remove::<Piece>()
-                .despawn();
+    for (mut sprite, mut visibility, tower_segment) in tower_segments.iter_mut() {
+        match game.board().get(tower_segment.x as i32, tower_segment.y as i32) {
+            // lines that are about to be cleared flash white and fade out
+            Some(_) if game.phase() == Phase::LineClear && game.clearing_lines().contains(&tower_segment.y) => {
+                sprite.color = Color::rgba(1.0, 1.0, 1.0, 1.0 - game.delay_progress());
+                visibility.is_visible = true;
+            },
+            Some(shape) => {
+                sprite.color = Color::from(shape.get_color(game.pieces()));
+                visibility.is_visible = true;
+            },
+            None => { visibility.is_visible = false; }
         }
-
-        play_sound_events.send(PlaySoundEvent { sound: Sound::GameOver });
-
-        // reset score
-        score_resource.reset();
-
-        // reset level to starting level for quick restart ig
-        game_state.level = game_state.starting_level;
-
-        // spawn new piece
-        game_state.has_piece = false;
     }
 }
 
+/// Runs the game engine, which contains all of the rules, and displays its state
 pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app
+            .add_asset::<PieceSetAsset>()
+            .init_asset_loader::<PieceSetLoader>()
             .init_resource::<SegmentMaterialResource>()
-            .init_resource::<Events<PlacePieceEvent>>()
-            .init_resource::<Events<CheckLinesEvent>>()
-            .init_resource::<Events<LevelUpEvent>>()
-            .init_resource::<Events<LossEvent>>()
-            .insert_resource(PieceSelectionResource { next_piece: None })
-            .insert_resource(FallTimer(Timer::from_seconds(FALL_DELAY, true)))
-            .insert_resource(InputTimer(Timer::from_seconds(MOVE_DELAY, true)))
-            .insert_resource(DropInputTimer(Timer::from_seconds(DROP_DELAY, true)))
+            .init_resource::<PieceSetResource>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<Events<GameEvent>>()
+            .init_resource::<GameChanged>()
+            .init_resource::<BoardLayout>()
             .insert_resource(InputState::default())
-            .add_system(piece_movement_input.before(move_pieces))
-            .add_system(move_pieces)
-            .add_system(restart.before(place_piece))
-            .add_system(spawn_pieces.after(restart))
-            .add_system(check_lines.before(place_piece))
-            .add_system(level_up)
-            .add_system(place_piece.after(move_pieces));
+            .add_startup_system(spawn_segments)
+            .add_system(apply_piece_set.before(step_game))
+            .add_system(piece_movement_input.before(step_game))
+            .add_system(toggle_ghost_piece.before(step_game))
+            .add_system(step_game)
+            .add_system(sync_piece_segments.after(step_game))
+            .add_system(sync_ghost_segments.after(step_game))
+            .add_system(sync_tower_segments.after(step_game));
     }
-}
\ No newline at end of file
+}
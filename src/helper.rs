@@ -1,11 +1,45 @@
-use crate::consts::*;
+use bevy::prelude::*;
+use bevy_tetris::engine::{BoardConfig, Game};
+use crate::ProgramData;
 
-/// Turns a "board position" (ranging from 0-9; 0-19) into a transform translation position
-pub fn coords_to_world(x: f32, y: f32) -> (f32, f32) {
-    ((x - (BOARD_WIDTH as f32 / 2.0 - 0.5)) * SEGMENT_SIZE, (y - (BOARD_HEIGHT as f32 / 2.0 - 0.5)) * SEGMENT_SIZE)
+// UI
+/// Default size of a piece segment on the screen, the largest that segments get
+pub const SEGMENT_SIZE: f32 = 40.0;
+/// How much of the window's height the board can take up at most, the rest is for the bars above and below it
+pub const BOARD_SCREEN_FRACTION: f32 = 0.8;
+/// How opaque the ghost piece showing where the current piece would land is
+pub const GHOST_PIECE_ALPHA: f32 = 0.3;
+/// How long the game over message (with the score and seed of the lost game) stays on screen
+pub const GAME_OVER_DISPLAY_TIME: f32 = 5.0;
+/// How long special clears (like T-spins) are announced on screen
+pub const CLEAR_ANNOUNCEMENT_DISPLAY_TIME: f32 = 1.5;
+/// The size (in segments) of the box that the first upcoming piece is shown in
+pub const NEXT_PREVIEW_SEGMENTS: f32 = 4.0;
+/// How big the boxes of the other upcoming pieces are compared to the first one
+pub const QUEUED_PREVIEW_SCALE: f32 = 0.6;
+
+// RESOURCES
+/// How big the board is drawn on the screen
+pub struct BoardLayout {
+    /// Size of a piece segment on the screen
+    pub segment_size: f32
 }
+impl FromWorld for BoardLayout {
+    fn from_world(world: &mut World) -> Self {
+        let window_height = world.get_resource::<ProgramData>().unwrap().window_height;
+        let config = world.get_resource::<Game>().unwrap().board_config();
 
-// Turns a world position into a "board position" (clamped)
-pub fn coords_to_pixel(x: f32, y: f32) -> (i32, i32) {
-    ((x / SEGMENT_SIZE + (BOARD_WIDTH as f32 / 2.0 - 0.5)).round() as i32, (y / SEGMENT_SIZE + (BOARD_HEIGHT as f32 / 2.0 - 0.5)).round() as i32)
-}
\ No newline at end of file
+        BoardLayout::fit(config, window_height)
+    }
+}
+impl BoardLayout {
+    /// Shrinks the segments of tall boards so that the board fits in a window of the given height (in pixels)
+    pub fn fit(config: &BoardConfig, window_height: f32) -> Self {
+        BoardLayout { segment_size: SEGMENT_SIZE.min(window_height * BOARD_SCREEN_FRACTION / config.height.max(1) as f32) }
+    }
+}
+
+/// Turns a "board position" (ranging from 0 to the board's width / height - 1) into a transform translation position
+pub fn coords_to_world(config: &BoardConfig, layout: &BoardLayout, x: f32, y: f32) -> (f32, f32) {
+    ((x - (config.width as f32 / 2.0 - 0.5)) * layout.segment_size, (y - (config.height as f32 / 2.0 - 0.5)) * layout.segment_size)
+}
@@ -0,0 +1,5 @@
+//! The bevy-independent part of the game, which can be used to simulate games headlessly.
+//! Build with `--no-default-features` to leave out bevy entirely.
+
+pub mod consts;
+pub mod engine;
@@ -1,16 +1,19 @@
+// bevy systems often take many (and complex) queries and resources as parameters
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use std::sync::Arc;
+
 use bevy::prelude::*;
 use bevy::ecs::event::*;
 
-mod consts;
 mod helper;
 mod window;
 use window::WindowPlugin;
-mod board;
 mod game;
-use game::{GamePlugin, GameState};
-mod score;
+use game::GamePlugin;
+use bevy_tetris::engine::{Game, GameSettings, BoardConfig, Delays, HandlingSettings, LockReset, UpAction, GravityCurve, randomizer, rotation, scoring};
+use bevy_tetris::consts::{BOARD_WIDTH, BOARD_HEIGHT, INPUT_BUFFER_TIME, LOCK_DELAY, LOCK_RESET_MOVES};
 mod ui;
-use score::ScoreResource;
 use ui::UIPlugin;
 mod audio;
 use audio::AudioPlugin;
@@ -22,6 +25,9 @@ struct ProgramData {
 }
 
 fn main() {
+    // a seed can be given with --seed to play a specific piece sequence
+    let seed = std::env::args().skip_while(|arg| arg != "--seed").nth(1).and_then(|seed| seed.parse().ok());
+
     App::new()
         .insert_resource(WindowDescriptor {
             width: 1920.0,
@@ -32,13 +38,30 @@ fn main() {
             window_width: 1920.0,
             window_height: 1008.0
         })
-        .insert_resource(GameState { 
-            started: true, // TEMP
+        .insert_resource(Game::new(GameSettings { 
             starting_level: 1,
-            level: 1,
-            has_piece: false
-        })
-        .init_resource::<ScoreResource>()
+            board: BoardConfig::new(BOARD_WIDTH, BOARD_HEIGHT),
+            rotation_system: Arc::new(rotation::Srs),
+            randomizer: Box::new(randomizer::Bag::seven()),
+            scoring: Arc::new(scoring::Guideline),
+            gravity: GravityCurve::Guideline,
+            next_queue_length: 5,
+            hold_enabled: true,
+            up_action: UpAction::SonicDrop,
+            rotation_180_enabled: true,
+            irs: true,
+            ihs: true,
+            input_buffer: INPUT_BUFFER_TIME,
+            // every player can tune these to the speed they play at
+            handling: HandlingSettings::default(),
+            delays: vec![(0, Delays::default())],
+            lock_delay: LOCK_DELAY,
+            lock_reset: LockReset::Moves(LOCK_RESET_MOVES),
+            ghost_piece_enabled: true,
+            seed,
+            // the piece set gets replaced by the one in assets/pieces once it has loaded
+            ..Default::default()
+        }))
         .add_startup_system(setup)
         .add_plugins(DefaultPlugins)
         .add_plugin(WindowPlugin)
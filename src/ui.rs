@@ -1,8 +1,7 @@
-use crate::consts::*;
-use crate::score::ScoreResource;
-use crate::game::{PieceSelectionResource, GameState};
+use bevy_tetris::engine::{ Game, GameEvent, Shape, TSpin };
 use crate::ProgramData;
-use crate::board::{ Piece, Shape };
+use crate::helper::*;
+use crate::game::GameChanged;
 use bevy::prelude::*;
 
 // COMPONENTS
@@ -12,27 +11,79 @@ pub struct ScoreText;
 pub struct LinesText;
 #[derive(Component)]
 pub struct LevelText;
+/// Shows how many pieces in a row after the first one have cleared lines
+#[derive(Component)]
+pub struct ComboText;
+/// Shows how many difficult clears (tetrises and T-spins) in a row after the first one there have been
+#[derive(Component)]
+pub struct BackToBackText;
+/// Shows the grade that the score has earned, with scoring rules that have grades
+#[derive(Component)]
+pub struct GradeText;
+/// Shows the score and seed of the last lost game for a while after losing
+#[derive(Component)]
+pub struct GameOverText;
+/// Announces special clears (like T-spins and perfect clears) for a while after they happen
+#[derive(Component)]
+pub struct ClearAnnouncementText;
+/// A box in the next queue that shows one of the upcoming pieces
+#[derive(Component)]
+pub struct NextPiecePreview {
+    index: usize
+}
+/// One of the segments of an upcoming piece
 #[derive(Component)]
 pub struct NextPieceImage {
-    x: i32,
-    y: i32
+    preview: usize,
+    segment_index: usize
 }
+/// The box that shows the held piece
+#[derive(Component)]
+pub struct HoldPiecePreview;
+/// One of the segments of the held piece
+#[derive(Component)]
+pub struct HoldPieceImage {
+    segment_index: usize
+}
+
+// RESOURCES
+/// Counts down until the game over text gets hidden
+struct GameOverTimer(Timer);
+/// Counts down until the clear announcement text gets hidden
+struct ClearAnnouncementTimer(Timer);
 
 pub struct UIPlugin;
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
         app
+            .insert_resource(GameOverTimer(Timer::from_seconds(GAME_OVER_DISPLAY_TIME, false)))
+            .insert_resource(ClearAnnouncementTimer(Timer::from_seconds(CLEAR_ANNOUNCEMENT_DISPLAY_TIME, false)))
             .add_startup_system(setup_ui)
+            .add_system(update_game_over_text)
+            .add_system(update_clear_announcement_text)
             .add_system(update_score_text)
             .add_system(update_level_text)
+            .add_system(update_combo_text)
+            .add_system(update_back_to_back_text)
+            .add_system(update_grade_text)
             .add_system(update_next_piece_display)
+            .add_system(update_hold_piece_display)
             .add_system(update_lines_text);
     }
 }
 
-fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<ProgramData>) {
+fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<ProgramData>, game: Res<Game>, layout: Res<BoardLayout>) {
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
 
+    // the size of the board on the screen
+    let config = game.board_config();
+    let board_width = config.width as f32 * layout.segment_size;
+    let board_height = config.height as f32 * layout.segment_size;
+    // the next queue goes right next to the board, with the score and level after it
+    let queue_left = (pd.window_width - board_width) / 2.0 + board_width + 60.0;
+    // the hold box goes on the other side of the board
+    let hold_left = (pd.window_width - board_width) / 2.0 - 60.0 - next_preview_size(&layout, 0);
+
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -43,7 +94,7 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
                     ..Default::default()
                 },
                 size: Size {
-                    width: Val::Px((pd.window_width - (BOARD_WIDTH as f32 * SEGMENT_SIZE)) / 2.0),
+                    width: Val::Px((pd.window_width - board_width) / 2.0),
                     height: Val::Px(pd.window_height)
                 },
                 ..Default::default()
@@ -61,7 +112,7 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
                     ..Default::default()
                 },
                 size: Size {
-                    width: Val::Px((pd.window_width - (BOARD_WIDTH as f32 * SEGMENT_SIZE)) / 2.0),
+                    width: Val::Px((pd.window_width - board_width) / 2.0),
                     height: Val::Px(pd.window_height)
                 },
                 ..Default::default()
@@ -75,7 +126,7 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
             style: Style {
                 position_type: PositionType::Absolute,
                 position: Rect {
-                    left: Val::Px((pd.window_width - (BOARD_WIDTH as f32 * SEGMENT_SIZE)) / 2.0 + (BOARD_WIDTH as f32 * SEGMENT_SIZE) + 100.0),
+                    left: Val::Px(queue_left + next_preview_size(&layout, 0) + 100.0),
                     bottom: Val::Px(pd.window_height / 2.0),
                     ..Default::default()
                 },
@@ -121,6 +172,72 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
                     ..Default::default()
                 })
                 .insert(LevelText);
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            top: Val::Px(200.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        "Combo: 0",
+                        TextStyle {
+                            font_size: 40.0,
+                            font: font.clone(),
+                            color: Color::rgb(0.9, 0.9, 0.9)
+                        },
+                        Default::default()
+                    ),
+                    ..Default::default()
+                })
+                .insert(ComboText);
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            top: Val::Px(300.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        "Back-to-back: 0",
+                        TextStyle {
+                            font_size: 40.0,
+                            font: font.clone(),
+                            color: Color::rgb(0.9, 0.9, 0.9)
+                        },
+                        Default::default()
+                    ),
+                    ..Default::default()
+                })
+                .insert(BackToBackText);
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            top: Val::Px(400.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font_size: 40.0,
+                            font: font.clone(),
+                            color: Color::rgb(0.9, 0.9, 0.9)
+                        },
+                        Default::default()
+                    ),
+                    ..Default::default()
+                })
+                .insert(GradeText);
         })
         .commands()
         // BOTTOM BAR NODE
@@ -128,12 +245,12 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
             style: Style {
                 position_type: PositionType::Absolute,
                 position: Rect {
-                    left: Val::Px((pd.window_width - (BOARD_WIDTH as f32 * SEGMENT_SIZE)) / 2.0),
+                    left: Val::Px((pd.window_width - board_width) / 2.0),
                     ..Default::default()
                 },
                 size: Size {
-                    width: Val::Px(BOARD_WIDTH as f32 * SEGMENT_SIZE),
-                    height: Val::Px((pd.window_height - (BOARD_HEIGHT as f32 * SEGMENT_SIZE)) / 2.0)
+                    width: Val::Px(board_width),
+                    height: Val::Px((pd.window_height - board_height) / 2.0)
                 },
                 ..Default::default()
             },
@@ -146,13 +263,13 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
             style: Style {
                 position_type: PositionType::Absolute,
                 position: Rect {
-                    left: Val::Px((pd.window_width - (BOARD_WIDTH as f32 * SEGMENT_SIZE)) / 2.0),
-                    bottom: Val::Px(BOARD_HEIGHT as f32 * SEGMENT_SIZE + ((pd.window_height - BOARD_HEIGHT as f32 * SEGMENT_SIZE) / 2.0)),
+                    left: Val::Px((pd.window_width - board_width) / 2.0),
+                    bottom: Val::Px(board_height + ((pd.window_height - board_height) / 2.0)),
                     ..Default::default()
                 },
                 size: Size {
-                    width: Val::Px(BOARD_WIDTH as f32 * SEGMENT_SIZE),
-                    height: Val::Px((pd.window_height - (BOARD_HEIGHT as f32 * SEGMENT_SIZE)) / 2.0)
+                    width: Val::Px(board_width),
+                    height: Val::Px((pd.window_height - board_height) / 2.0)
                 },
                 ..Default::default()
             },
@@ -164,7 +281,7 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
                 .spawn_bundle(TextBundle {
                     style: Style {
                         position: Rect {
-                            left: Val::Px((BOARD_WIDTH / 2 - 1) as f32 * SEGMENT_SIZE),
+                            left: Val::Px((config.width / 2).saturating_sub(1) as f32 * layout.segment_size),
                             bottom: Val::Px(30.0),
                             ..Default::default()
                         },
@@ -184,21 +301,52 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
                 .insert(LinesText);
         })
         .commands()
-        // NEXT PIECE TEXT & DISPLAY NODE
+        // CLEAR ANNOUNCEMENT TEXT NODE
         .spawn_bundle(NodeBundle {
             style: Style {
                 position_type: PositionType::Absolute,
                 position: Rect {
-                    left: Val::Px((pd.window_width - (BOARD_WIDTH as f32 * SEGMENT_SIZE)) / 2.0 + (BOARD_WIDTH as f32 * SEGMENT_SIZE) + 100.0),
-                    bottom: Val::Px(pd.window_height / 2.0 + SEGMENT_SIZE * 5.0 + 20.0),
+                    left: Val::Px(hold_left),
+                    bottom: Val::Px(pd.window_height / 2.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font_size: 40.0,
+                            font: font.clone(),
+                            color: Color::rgb(0.9, 0.9, 0.9)
+                        },
+                        Default::default()
+                    ),
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(ClearAnnouncementText);
+        })
+        .commands()
+        // GAME OVER TEXT NODE
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px((pd.window_width - board_width) / 2.0),
+                    bottom: Val::Px(pd.window_height / 2.0),
                     ..Default::default()
                 },
-                flex_wrap: FlexWrap::Wrap,
-                flex_basis: Val::Px(SEGMENT_SIZE),
                 size: Size {
-                    width: Val::Px(SEGMENT_SIZE * 4.0),
-                    height: Val::Px(SEGMENT_SIZE * 4.0)
+                    width: Val::Px(board_width),
+                    ..Default::default()
                 },
+                justify_content: JustifyContent::Center,
                 ..Default::default()
             },
             color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
@@ -208,7 +356,54 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
             parent
                 .spawn_bundle(TextBundle {
                     text: Text::with_section(
-                        "Next piece",
+                        "",
+                        TextStyle {
+                            font_size: 40.0,
+                            font: font.clone(),
+                            color: Color::rgb(0.9, 0.9, 0.9)
+                        },
+                        TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            ..Default::default()
+                        }
+                    ),
+                    visibility: Visibility { is_visible: false },
+                    ..Default::default()
+                })
+                .insert(GameOverText);
+        })
+        .commands()
+        // NEXT QUEUE TEXT & DISPLAY NODE
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(queue_left),
+                    top: Val::Px((pd.window_height - board_height) / 2.0),
+                    ..Default::default()
+                },
+                size: Size {
+                    width: Val::Px(next_preview_size(&layout, 0)),
+                    height: Val::Px(board_height)
+                },
+                ..Default::default()
+            },
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            top: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        "Next",
                         TextStyle {
                             font_size: 40.0,
                             font: font.clone(),
@@ -219,106 +414,346 @@ fn setup_ui(mut commands: Commands, asset_server: ResMut<AssetServer>, pd: Res<P
                     ..Default::default()
                 });
 
-            for y in 0..4 {
-                for x in 0..4 {
-                    parent
-                        .spawn_bundle(ImageBundle {
-                            style: Style {
-                                position: Rect {
-                                    bottom: Val::Px(-SEGMENT_SIZE * 5.0 - 20.0),
-                                    ..Default::default()
-                                },
-                                size: Size {
-                                    width: Val::Px(SEGMENT_SIZE),
-                                    height: Val::Px(SEGMENT_SIZE)
-                                },
+            // every upcoming piece gets its own box below the previous one
+            let mut top = 60.0;
+            for index in 0..game.next_queue_length() {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: Rect {
+                                top: Val::Px(top),
                                 ..Default::default()
                             },
-                            image: UiImage(asset_server.load("textures/segment.png")),
-                            color: UiColor(Color::rgba(1.0, 1.0, 1.0, 1.0)),
+                            size: Size {
+                                width: Val::Px(next_preview_size(&layout, index)),
+                                height: Val::Px(next_preview_size(&layout, index))
+                            },
                             ..Default::default()
-                        })
-                        .insert(NextPieceImage { x, y });
-                }
+                        },
+                        color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+                        ..Default::default()
+                    })
+                    .insert(NextPiecePreview { index })
+                    .with_children(|parent| {
+                        for segment_index in 0..game.pieces().max_segments() {
+                            parent
+                                .spawn_bundle(next_piece_image_bundle(&asset_server))
+                                .insert(NextPieceImage { preview: index, segment_index });
+                        }
+                    });
+
+                top += next_preview_size(&layout, index) + 20.0;
             }
         });
+
+    if !game.hold_enabled() {
+        return;
+    }
+
+    // HOLD TEXT & DISPLAY NODE
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(hold_left),
+                    top: Val::Px((pd.window_height - board_height) / 2.0),
+                    ..Default::default()
+                },
+                size: Size {
+                    width: Val::Px(next_preview_size(&layout, 0)),
+                    height: Val::Px(next_preview_size(&layout, 0) + 60.0)
+                },
+                ..Default::default()
+            },
+            color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            top: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    text: Text::with_section(
+                        "Hold",
+                        TextStyle {
+                            font_size: 40.0,
+                            font: font.clone(),
+                            color: Color::rgb(0.9, 0.9, 0.9)
+                        },
+                        Default::default()
+                    ),
+                    ..Default::default()
+                });
+
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            top: Val::Px(60.0),
+                            ..Default::default()
+                        },
+                        size: Size {
+                            width: Val::Px(next_preview_size(&layout, 0)),
+                            height: Val::Px(next_preview_size(&layout, 0))
+                        },
+                        ..Default::default()
+                    },
+                    color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+                    ..Default::default()
+                })
+                .insert(HoldPiecePreview)
+                .with_children(|parent| {
+                    for segment_index in 0..game.pieces().max_segments() {
+                        parent
+                            .spawn_bundle(next_piece_image_bundle(&asset_server))
+                            .insert(HoldPieceImage { segment_index });
+                    }
+                });
+        });
+}
+
+/// The size of the box that the upcoming piece at the given index of the next queue is shown in, pieces further back are smaller.
+/// The boxes scale with the board's segments
+fn next_preview_size(layout: &BoardLayout, index: usize) -> f32 {
+    let scale = if index == 0 { 1.0 } else { QUEUED_PREVIEW_SCALE };
+    layout.segment_size * NEXT_PREVIEW_SEGMENTS * scale
+}
+
+/// Creates a hidden image for a segment of an upcoming piece, it gets positioned when the piece is known
+fn next_piece_image_bundle(asset_server: &AssetServer) -> ImageBundle {
+    ImageBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            ..Default::default()
+        },
+        image: UiImage(asset_server.load("textures/segment.png")),
+        color: UiColor(Color::rgba(0.0, 0.0, 0.0, 0.0)),
+        ..Default::default()
+    }
 }
 
-fn update_score_text(score: Res<ScoreResource>, mut score_text_query: Query<(&mut Text, &ScoreText)>) {
-    if !score.is_changed() {
+fn update_score_text(game: Res<Game>, game_changed: Res<GameChanged>, mut score_text_query: Query<(&mut Text, &ScoreText)>) {
+    if !game_changed.is_changed() {
         return;
     }
 
     for (mut text, _) in score_text_query.iter_mut() {
         text.sections[0].value = format!(
             "Score: {}",
-            score.score()
+            game.score().score()
         );
     }
 }
 
-fn update_level_text(game_state: Res<GameState>, mut level_text_query: Query<(&mut Text, &LevelText)>) {
-    if !game_state.is_changed() {
+fn update_level_text(game: Res<Game>, game_changed: Res<GameChanged>, mut level_text_query: Query<(&mut Text, &LevelText)>) {
+    if !game_changed.is_changed() {
         return;
     }
 
     for (mut text, _) in level_text_query.iter_mut() {
         text.sections[0].value = format!(
             "Level: {}",
-            game_state.level
+            game.level()
         );
     }
 }
 
-fn update_lines_text(score: Res<ScoreResource>, mut lines_text_query: Query<(&mut Text, &LinesText)>) {
-    if !score.is_changed() {
+fn update_combo_text(game: Res<Game>, game_changed: Res<GameChanged>, mut combo_text_query: Query<(&mut Text, &ComboText)>) {
+    if !game_changed.is_changed() {
+        return;
+    }
+
+    for (mut text, _) in combo_text_query.iter_mut() {
+        text.sections[0].value = format!(
+            "Combo: {}",
+            game.score().combo()
+        );
+    }
+}
+
+fn update_back_to_back_text(game: Res<Game>, game_changed: Res<GameChanged>, mut back_to_back_text_query: Query<(&mut Text, &BackToBackText)>) {
+    if !game_changed.is_changed() {
+        return;
+    }
+
+    for (mut text, _) in back_to_back_text_query.iter_mut() {
+        text.sections[0].value = format!(
+            "Back-to-back: {}",
+            game.score().back_to_back()
+        );
+    }
+}
+
+fn update_grade_text(game: Res<Game>, game_changed: Res<GameChanged>, mut grade_text_query: Query<(&mut Text, &GradeText)>) {
+    if !game_changed.is_changed() {
+        return;
+    }
+
+    for (mut text, _) in grade_text_query.iter_mut() {
+        text.sections[0].value = match game.grade() {
+            Some(grade) => format!("Grade: {}", grade),
+            None => String::new()
+        };
+    }
+}
+
+fn update_lines_text(game: Res<Game>, game_changed: Res<GameChanged>, mut lines_text_query: Query<(&mut Text, &LinesText)>) {
+    if !game_changed.is_changed() {
         return;
     }
 
     for (mut text, _) in lines_text_query.iter_mut() {
         text.sections[0].value = format!(
             "Lines: {}",
-            score.lines()
+            game.score().lines()
         );
     }
 }
 
-fn update_next_piece_display(piece_selection: Res<PieceSelectionResource>, mut images: Query<(&mut UiColor, &NextPieceImage)>) {
-    if !piece_selection.is_changed() {
+fn update_game_over_text(time: Res<Time>, mut game_over_timer: ResMut<GameOverTimer>, mut game_events: EventReader<GameEvent>, mut game_over_text_query: Query<(&mut Text, &mut Visibility, &GameOverText)>) {
+    for event in game_events.iter() {
+        if let GameEvent::Loss { score, seed } = event {
+            for (mut text, mut visibility, _) in game_over_text_query.iter_mut() {
+                text.sections[0].value = format!(
+                    "Game over!\nScore: {}\nSeed: {}",
+                    score, seed
+                );
+                visibility.is_visible = true;
+            }
+            game_over_timer.0.reset();
+        }
+    }
+
+    if game_over_timer.0.tick(time.delta()).just_finished() {
+        for (_, mut visibility, _) in game_over_text_query.iter_mut() {
+            visibility.is_visible = false;
+        }
+    }
+}
+
+fn update_clear_announcement_text(time: Res<Time>, mut announcement_timer: ResMut<ClearAnnouncementTimer>, mut game_events: EventReader<GameEvent>, mut announcement_text_query: Query<(&mut Text, &mut Visibility, &ClearAnnouncementText)>) {
+    // a single piece can make more than one special clear (a T-spin that is also a perfect clear), so announce all of them together
+    let announcements: Vec<String> = game_events.iter().filter_map(|event| match event {
+        GameEvent::TSpin(t_spin, lines) => Some(format!(
+            "{}T-SPIN{}",
+            if *t_spin == TSpin::Mini { "MINI " } else { "" },
+            match lines { 0 => "", 1 => "\nSINGLE", 2 => "\nDOUBLE", _ => "\nTRIPLE" }
+        )),
+        GameEvent::PerfectClear(_) => Some("PERFECT\nCLEAR".to_string()),
+        _ => None
+    }).collect();
+
+    if !announcements.is_empty() {
+        for (mut text, mut visibility, _) in announcement_text_query.iter_mut() {
+            text.sections[0].value = announcements.join("\n");
+            visibility.is_visible = true;
+        }
+        announcement_timer.0.reset();
+    }
+
+    if announcement_timer.0.tick(time.delta()).just_finished() {
+        for (_, mut visibility, _) in announcement_text_query.iter_mut() {
+            visibility.is_visible = false;
+        }
+    }
+}
+
+fn update_next_piece_display(mut commands: Commands, asset_server: Res<AssetServer>, game: Res<Game>, game_changed: Res<GameChanged>, layout: Res<BoardLayout>, previews: Query<(Entity, &NextPiecePreview)>, mut images: Query<(&mut UiColor, &mut Style, &NextPieceImage)>) {
+    if !game_changed.is_changed() {
         return;
     }
 
-    let next_piece: &Piece;
+    // make sure every preview has enough images for the largest piece (the piece set can change after the previews are made)
+    for (entity, preview) in previews.iter() {
+        let image_count = images.iter().filter(|(_, _, image)| image.preview == preview.index).count();
+        commands.entity(entity).with_children(|parent| {
+            for segment_index in image_count..game.pieces().max_segments() {
+                parent
+                    .spawn_bundle(next_piece_image_bundle(&asset_server))
+                    .insert(NextPieceImage { preview: preview.index, segment_index });
+            }
+        });
+    }
+
+    for (mut image_color, mut style, image) in images.iter_mut() {
+        let next_piece = game.piece_selection().next_pieces.get(image.preview);
+        show_preview_segment(&game, next_piece.map(|piece| (piece.shape, piece.rotation)), image.segment_index, next_preview_size(&layout, image.preview), &mut image_color, &mut style);
+    }
+}
+
+fn update_hold_piece_display(mut commands: Commands, asset_server: Res<AssetServer>, game: Res<Game>, game_changed: Res<GameChanged>, layout: Res<BoardLayout>, previews: Query<Entity, With<HoldPiecePreview>>, mut images: Query<(&mut UiColor, &mut Style, &HoldPieceImage)>) {
+    if !game_changed.is_changed() {
+        return;
+    }
 
-    match &piece_selection.next_piece {
-        Some(piece) => { next_piece = piece; },
-        // no piece selected, hide all the tiles
-        None => {
-            for (mut image_color, _) in images.iter_mut() {
-                image_color.0 = Color::rgba(0.0, 0.0, 0.0, 0.0);
+    // make sure there are enough images for the largest piece
+    let image_count = images.iter().count();
+    for entity in previews.iter() {
+        commands.entity(entity).with_children(|parent| {
+            for segment_index in image_count..game.pieces().max_segments() {
+                parent
+                    .spawn_bundle(next_piece_image_bundle(&asset_server))
+                    .insert(HoldPieceImage { segment_index });
             }
-            return;
-        }
+        });
     }
 
-    // get all the image tiles that should be shown for the piece
-    let mut filled_positions: Vec<Vec2> = vec![];
+    for (mut image_color, mut style, image) in images.iter_mut() {
+        show_preview_segment(&game, game.held_shape().map(|shape| (shape, 0)), image.segment_index, next_preview_size(&layout, 0), &mut image_color, &mut style);
 
-    for i in 0..=3 {
-        // get the rotated position of each segment of the next piece
-        // NOTE: the positions will be given an offset of (1; 1) due to negative offsets
-        // NOTE: the I piece has special offsets
-        filled_positions.push(next_piece.shape.get_rotation_offset(i, next_piece.rotation) 
-        + match next_piece.shape { Shape::I => { if next_piece.rotation % 2 == 0 { Vec2::new(1.0, 0.0) } else { Vec2::new(2.0, 1.0) }}, _ => Vec2::ONE });
+        // the held piece is greyed out while it can't be swapped
+        if !game.can_hold() && image_color.0.a() > 0.0 {
+            image_color.0 = Color::rgb(0.4, 0.4, 0.4);
+        }
     }
+}
 
-    // hide or show tiles depending on if they contain the next piece's segments or not
-    for (mut image_color, piece_image) in images.iter_mut() {
-        if filled_positions.contains(&Vec2::new(piece_image.x as f32, piece_image.y as f32)) {
-            image_color.0 = next_piece.shape.get_color();
+/// Positions and colors the image of one segment of a piece (given as shape and rotation) that is shown in a preview box of the given size.
+/// The piece's bounding box is centred in the box, and the segments are shrunk if the piece would not fit
+fn show_preview_segment(game: &Game, piece: Option<(Shape, usize)>, segment_index: usize, preview_size: f32, image_color: &mut UiColor, style: &mut Style) {
+    let (shape, rotation) = match piece {
+        Some(piece) => piece,
+        // no piece to show, hide the tile
+        None => {
+            image_color.0 = Color::rgba(0.0, 0.0, 0.0, 0.0);
+            return;
         }
-        else {
+    };
+
+    let offsets = game.rotation_system().get_rotation_state(game.pieces(), shape, rotation);
+    let (x, y) = match offsets.get(segment_index) {
+        Some(&offset) => offset,
+        None => {
             image_color.0 = Color::rgba(0.0, 0.0, 0.0, 0.0);
+            return;
         }
-    }
-}
\ No newline at end of file
+    };
+
+    // NOTE: the positions are moved so that the lowest X and Y are 0, because offsets can be negative
+    let min_x = offsets.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = offsets.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let width = (offsets.iter().map(|&(x, _)| x).max().unwrap_or(0) - min_x + 1) as f32;
+    let height = (offsets.iter().map(|&(_, y)| y).max().unwrap_or(0) - min_y + 1) as f32;
+
+    let segment_size = preview_size / NEXT_PREVIEW_SEGMENTS.max(width).max(height);
+
+    style.position = Rect {
+        left: Val::Px((preview_size - width * segment_size) / 2.0 + (x - min_x) as f32 * segment_size),
+        bottom: Val::Px((preview_size - height * segment_size) / 2.0 + (y - min_y) as f32 * segment_size),
+        ..Default::default()
+    };
+    style.size = Size {
+        width: Val::Px(segment_size),
+        height: Val::Px(segment_size)
+    };
+    image_color.0 = Color::from(shape.get_color(game.pieces()));
+}